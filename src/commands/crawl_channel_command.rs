@@ -1,5 +1,64 @@
+use mongodb::bson::{doc, Document};
+
+#[derive(Debug, Clone)]
+pub enum DiscoverySource {
+    Additional,
+    Subscription { channel_id: String },
+    FeaturedChannel { channel_id: String },
+    Mention { channel_id: String },
+    Search { term: String },
+}
+
+#[derive(Debug, Clone)]
+pub struct Discovery {
+    pub source: DiscoverySource,
+    pub depth: Option<u32>,
+    pub run_id: Option<String>,
+}
+
+impl DiscoverySource {
+    pub fn to_document(&self) -> Document {
+        match self {
+            DiscoverySource::Additional => doc! {"type": "additional"},
+            DiscoverySource::Subscription { channel_id } => {
+                doc! {"type": "subscription", "channel": channel_id}
+            }
+            DiscoverySource::FeaturedChannel { channel_id } => {
+                doc! {"type": "featured", "channel": channel_id}
+            }
+            DiscoverySource::Mention { channel_id } => {
+                doc! {"type": "mention", "channel": channel_id}
+            }
+            DiscoverySource::Search { term } => doc! {"type": "search", "term": term},
+        }
+    }
+}
+
+/// Scheduling priority of a channel scrape, ordered from most to least urgent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrawlPriority {
+    Interactive,
+    Update,
+    Discovery,
+}
+
+impl CrawlPriority {
+    pub const COUNT: usize = 3;
+
+    pub fn lane(&self) -> usize {
+        match self {
+            CrawlPriority::Interactive => 0,
+            CrawlPriority::Update => 1,
+            CrawlPriority::Discovery => 2,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct CrawlChannelCommand {
     pub channel_id: String,
     pub ignore_sailing_terms: bool,
+    pub discovery: Option<Discovery>,
+    pub submission_id: Option<String>,
+    pub priority: CrawlPriority,
 }
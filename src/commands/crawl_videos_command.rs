@@ -1,4 +1,5 @@
 #[derive(Debug)]
 pub struct CrawlVideosCommand {
     pub channel_id: String,
+    pub backfill: bool,
 }
@@ -1,59 +1,234 @@
 use anyhow::Error;
-use log::info;
+use futures::stream::TryStreamExt;
+use log::{info, warn};
+use mongodb::bson::DateTime;
+use std::fmt;
 use std::time::Duration;
 use tokio::sync::mpsc::Sender;
-use tokio::time::sleep;
+use tokio::time::{sleep, timeout, Instant};
 
-use crate::commands::crawl_channel_command::CrawlChannelCommand;
-use crate::repos::additional_channel_repo::AdditionalChannelRepository;
+use crate::commands::crawl_channel_command::{
+    CrawlChannelCommand, CrawlPriority, Discovery, DiscoverySource,
+};
+use crate::models::config::AdditionalConfig;
+use crate::repos::additional_channel_repo::{AdditionalChannelRepository, SubmissionStatus};
+use crate::services::youtube_service::YoutubeService;
+use crate::utils::channel_reference_utils::{is_valid_channel_id, parse_channel_reference};
 
 const TEN_MINUTES_IN_SECONDS: u64 = 10 * 60;
+// Submissions wait in the channel scrape queue while processing, so only those
+// processing for much longer than any queue delay are sent again.
+const STUCK_PROCESSING_AFTER_MILLIS: i64 = 24 * 60 * 60 * 1000;
+const MAX_REOPEN_BACKOFF_SECONDS: u64 = 6 * 60 * 60;
+
+/// Doubles the wait between attempts to reopen the change stream, starting at ten
+/// minutes.
+fn get_reopen_backoff_seconds(failures: u32) -> u64 {
+    (TEN_MINUTES_IN_SECONDS << (failures.clamp(1, 16) - 1)).min(MAX_REOPEN_BACKOFF_SECONDS)
+}
+
+/// The submission can never be resolved to a channel, as opposed to transient
+/// errors like network failures or an exhausted quota.
+#[derive(Debug)]
+struct InvalidSubmissionError {
+    reason: String,
+}
+
+impl fmt::Display for InvalidSubmissionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl std::error::Error for InvalidSubmissionError {}
+
+fn invalid_submission(reason: &str) -> Error {
+    InvalidSubmissionError {
+        reason: reason.to_string(),
+    }
+    .into()
+}
 
 pub struct AdditionalChannelCrawler {
     sender: Sender<CrawlChannelCommand>,
     additional_channel_repo: AdditionalChannelRepository,
+    youtube_service: YoutubeService,
+    config: AdditionalConfig,
 }
 
 impl AdditionalChannelCrawler {
     pub fn new(
         sender: Sender<CrawlChannelCommand>,
         additional_channel_repo: AdditionalChannelRepository,
+        youtube_service: YoutubeService,
+        config: AdditionalConfig,
     ) -> AdditionalChannelCrawler {
         AdditionalChannelCrawler {
             sender,
             additional_channel_repo,
+            youtube_service,
+            config,
         }
     }
 
+    /// Waits for new submissions on a change stream. While the change stream is not
+    /// available the crawler polls, and tries to reopen the stream with a backoff.
     pub async fn crawl(&self) -> Result<(), Error> {
-        loop {
-            info!("Start additional channel crawler");
-            let additional_channels = self.additional_channel_repo.get_all().await?;
+        let mut change_stream = None;
+        let mut reopen_failures: u32 = 0;
+        let mut next_reopen_at = Instant::now();
+        let reopen_delay = Duration::from_secs(TEN_MINUTES_IN_SECONDS);
 
-            info!("Found {} additional channels", additional_channels.len());
+        loop {
+            if change_stream.is_none() && Instant::now() >= next_reopen_at {
+                match self.additional_channel_repo.watch_submissions().await {
+                    Ok(stream) => {
+                        info!("Watch additional channel submissions");
+                        change_stream = Some(stream);
+                        reopen_failures = 0;
+                    }
+                    Err(e) => {
+                        reopen_failures += 1;
+                        let backoff_seconds = get_reopen_backoff_seconds(reopen_failures);
+                        next_reopen_at = Instant::now() + Duration::from_secs(backoff_seconds);
 
-            for additional_channel in additional_channels {
-                let channel_id = additional_channel.get_str("_id")?.to_string();
-                let ignore_sailing_terms = additional_channel.get_bool("ignoreSailingTerm")?;
+                        warn!(
+                            "Change streams not available, fall back to polling and retry in {} seconds: {}",
+                            backoff_seconds, e
+                        );
+                    }
+                }
+            }
 
-                info!("Send additional channel for crawling: {}", channel_id);
+            self.process_pending_channels().await?;
 
-                let cmd = CrawlChannelCommand {
-                    channel_id: channel_id.clone(),
-                    ignore_sailing_terms,
-                };
+            match change_stream.as_mut() {
+                Some(stream) => {
+                    let wait_result = timeout(
+                        Duration::from_secs(TEN_MINUTES_IN_SECONDS),
+                        stream.try_next(),
+                    )
+                    .await;
 
-                self.sender.send(cmd).await?;
+                    match wait_result {
+                        Ok(Ok(Some(_))) => info!("New additional channel submitted"),
+                        Ok(Ok(None)) => {
+                            warn!("Change stream closed, fall back to polling");
+                            change_stream = None;
+                            next_reopen_at = Instant::now() + reopen_delay;
+                        }
+                        Ok(Err(e)) => {
+                            warn!("Change stream failed, fall back to polling: {}", e);
+                            change_stream = None;
+                            next_reopen_at = Instant::now() + reopen_delay;
+                        }
+                        Err(_) => {}
+                    }
+                }
+                None => {
+                    info!(
+                        "Wait for {} seconds until next crawl",
+                        TEN_MINUTES_IN_SECONDS
+                    );
 
-                self.additional_channel_repo.delete_one(&channel_id).await?;
+                    sleep(Duration::from_secs(TEN_MINUTES_IN_SECONDS)).await;
+                }
             }
+        }
+    }
+
+    async fn process_pending_channels(&self) -> Result<(), Error> {
+        info!("Start additional channel crawler");
+
+        let stuck_processing_before = DateTime::from_millis(
+            DateTime::now().timestamp_millis() - STUCK_PROCESSING_AFTER_MILLIS,
+        );
+        let additional_channels = self
+            .additional_channel_repo
+            .get_pending(stuck_processing_before)
+            .await?;
 
-            info!(
-                "Wait for {} seconds until next crawl",
-                TEN_MINUTES_IN_SECONDS
-            );
+        info!("Found {} additional channels", additional_channels.len());
 
-            sleep(Duration::from_secs(TEN_MINUTES_IN_SECONDS)).await;
+        for additional_channel in additional_channels {
+            let submission_id = additional_channel.get_str("_id")?.to_string();
+            let ignore_sailing_terms = additional_channel.get_bool("ignoreSailingTerm")?;
+
+            let channel_id = match self.resolve_channel_id(&submission_id).await {
+                Ok(channel_id) => channel_id,
+                Err(e) => {
+                    warn!(
+                        "Failed to resolve additional channel {}: {}",
+                        submission_id, e
+                    );
+
+                    // Transient errors leave the submission pending for the next poll.
+                    if e.downcast_ref::<InvalidSubmissionError>().is_none() {
+                        continue;
+                    }
+
+                    self.additional_channel_repo
+                        .set_finished(
+                            &submission_id,
+                            SubmissionStatus::Failed,
+                            Some(e.to_string()),
+                        )
+                        .await?;
+
+                    continue;
+                }
+            };
+
+            info!("Send additional channel for crawling: {}", channel_id);
+
+            self.additional_channel_repo
+                .set_processing(&submission_id, &channel_id)
+                .await?;
+
+            let cmd = CrawlChannelCommand {
+                channel_id,
+                ignore_sailing_terms,
+                discovery: Some(Discovery {
+                    source: DiscoverySource::Additional,
+                    depth: None,
+                    run_id: None,
+                }),
+                submission_id: Some(submission_id),
+                priority: CrawlPriority::Interactive,
+            };
+
+            self.sender.send(cmd).await?;
         }
+
+        let deleted_count = self
+            .additional_channel_repo
+            .delete_finished_before(self.config.retention_days)
+            .await?;
+
+        info!("Deleted {} finished additional channels", deleted_count);
+
+        Ok(())
+    }
+
+    /// Normalizes a submitted channel id, handle, channel url or video url into a
+    /// channel id.
+    async fn resolve_channel_id(&self, submission: &str) -> Result<String, Error> {
+        let reference = parse_channel_reference(submission)
+            .ok_or_else(|| invalid_submission("Unsupported channel reference"))?;
+
+        let channel_id = self
+            .youtube_service
+            .resolve_channel_reference(&reference)
+            .await?
+            .ok_or_else(|| invalid_submission("Channel not found"))?;
+
+        if !is_valid_channel_id(&channel_id) {
+            return Err(invalid_submission(&format!(
+                "Invalid channel id {}",
+                channel_id
+            )));
+        }
+
+        Ok(channel_id)
     }
 }
@@ -1,21 +1,53 @@
 // https://github.com/sailingchannels/crawler/blob/76b4442032e9062537576e98e37180c01293b412/discovery.py
 
 use crate::{
-    commands::crawl_channel_command::CrawlChannelCommand,
+    commands::crawl_channel_command::{
+        CrawlChannelCommand, CrawlPriority, Discovery, DiscoverySource,
+    },
+    models::{
+        config::DiscoveryConfig,
+        discovery_run::{DiscoveryRun, FrontierChannel, SeedProgress},
+        youtube_error_response::YoutubeApiError,
+    },
     repos::{
-        additional_channel_repo::AdditionalChannelRepository, channel_repo::ChannelRepository,
+        additional_channel_repo::AdditionalChannelRepository,
+        channel_edge_repo::{ChannelEdgeRepository, EdgeSource},
+        channel_repo::ChannelRepository,
+        discovery_seed_repo::{DiscoverySeedRepository, SubscriptionsStatus},
         settings_repo::SettingsRepository,
+        video_repo::VideoRepository,
+    },
+    services::{
+        sailing_terms_service::SailingTermsService,
+        youtube_service::{YoutubeService, SEARCH_QUOTA_COST},
+    },
+    utils::{
+        backoff_utils::get_backoff_seconds,
+        channel_reference_utils::{parse_channel_references, ChannelReference},
+        consts::ONE_DAYS_IN_SECONDS,
     },
-    services::{sailing_terms_service::SailingTermsService, youtube_service::YoutubeService},
-    utils::consts::ONE_DAYS_IN_SECONDS,
 };
 use anyhow::Error;
 use chrono::Utc;
-use log::info;
+use log::{error, info, warn};
+use mongodb::bson::oid::ObjectId;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 use tokio::sync::mpsc::Sender;
 use tokio::time::sleep;
 
+const SUBSCRIPTION_FORBIDDEN: &str = "subscriptionForbidden";
+const QUOTA_EXCEEDED: &str = "quotaExceeded";
+const MAX_BACKOFF_DAYS: i64 = 64;
+const MENTION_PAGE_SIZE: i64 = 500;
+
+/// A channel accepted during discovery. Channels accepted from loaded channel details
+/// carry their featured channels, so expanding them needs no further details request.
+struct AcceptedChannel {
+    channel_id: String,
+    featured_channel_ids: Option<Vec<String>>,
+}
+
 pub struct ChannelDiscoveryCrawler {
     sender: Sender<CrawlChannelCommand>,
     channel_repo: ChannelRepository,
@@ -23,9 +55,14 @@ pub struct ChannelDiscoveryCrawler {
     youtube_service: YoutubeService,
     sailing_terms_service: SailingTermsService,
     additional_channel_repo: AdditionalChannelRepository,
+    video_repo: VideoRepository,
+    channel_edge_repo: ChannelEdgeRepository,
+    discovery_seed_repo: DiscoverySeedRepository,
+    config: DiscoveryConfig,
 }
 
 impl ChannelDiscoveryCrawler {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         sender: Sender<CrawlChannelCommand>,
         channel_repo: ChannelRepository,
@@ -33,6 +70,10 @@ impl ChannelDiscoveryCrawler {
         youtube_service: YoutubeService,
         sailing_terms_service: SailingTermsService,
         additional_channel_repo: AdditionalChannelRepository,
+        video_repo: VideoRepository,
+        channel_edge_repo: ChannelEdgeRepository,
+        discovery_seed_repo: DiscoverySeedRepository,
+        config: DiscoveryConfig,
     ) -> ChannelDiscoveryCrawler {
         ChannelDiscoveryCrawler {
             sender,
@@ -41,6 +82,10 @@ impl ChannelDiscoveryCrawler {
             youtube_service,
             sailing_terms_service,
             additional_channel_repo,
+            video_repo,
+            channel_edge_repo,
+            discovery_seed_repo,
+            config,
         }
     }
 
@@ -48,66 +93,741 @@ impl ChannelDiscoveryCrawler {
         println!("Start channel discovery crawler");
 
         loop {
-            if self.should_crawl().await.unwrap_or(false) {
-                let channel_ids = self.channel_repo.get_ids_upload_last_month(8000).await?;
-
-                for channel_id in channel_ids {
-                    info!("Check subscriptions of channel {}", channel_id);
-
-                    let subscriptions = self
-                        .youtube_service
-                        .get_channel_subscriptions(&channel_id)
-                        .await
-                        .unwrap_or(vec![]);
-
-                    for snippet in subscriptions {
-                        let sub_channel_id = snippet.resource_id.channel_id;
-                        
-                        let sailing_terms_result = self
-                            .sailing_terms_service
-                            .has_sailing_term(
-                                &sub_channel_id,
-                                &snippet.title,
-                                &snippet.description,
-                                false,
-                            )
+            let discovery_run = match self.settings_repo.get_discovery_run().await? {
+                Some(run) => {
+                    info!(
+                        "Resume discovery run {} at seed {} of {}",
+                        run.run_id,
+                        run.cursor,
+                        run.seeds.len()
+                    );
+                    Some(run)
+                }
+                None if self.should_crawl().await.unwrap_or(false) => {
+                    Some(self.start_discovery_run().await?)
+                }
+                None => None,
+            };
+
+            if let Some(run) = discovery_run {
+                // A failed run keeps its checkpoint and is continued in the next cycle.
+                match self.run_discovery(run).await {
+                    Ok(()) => {
+                        let crawl_timestamp = Utc::now().timestamp();
+                        self.settings_repo
+                            .set_last_discovery_crawl(crawl_timestamp)
                             .await;
+                    }
+                    Err(e) => error!("Discovery run failed: {}", e),
+                }
+            }
 
-                        let is_newly_discovered = self
-                            .is_channel_newly_discovered(&sub_channel_id)
-                            .await?;
+            info!("Wait for {} seconds until next crawl", ONE_DAYS_IN_SECONDS);
 
-                        let is_not_non_sailing_channel = self
-                            .sailing_terms_service
-                            .is_not_listed_as_non_sailing_channel(&sub_channel_id)
-                            .await;
+            sleep(Duration::from_secs(ONE_DAYS_IN_SECONDS)).await;
+        }
+    }
+
+    async fn start_discovery_run(&self) -> Result<DiscoveryRun, Error> {
+        let seeds = self.channel_repo.get_ids_upload_last_month(8000).await?;
+
+        let run = DiscoveryRun {
+            run_id: ObjectId::new().to_hex(),
+            seeds,
+            cursor: 0,
+            used_quota: 0,
+            started_at: Utc::now().timestamp(),
+            usable_seeds: 0,
+            private_seeds: 0,
+            failed_seeds: 0,
+            skipped_seeds: 0,
+            seed_progress: None,
+        };
 
-                        if is_newly_discovered
-                            && is_not_non_sailing_channel
-                            && sailing_terms_result.has_sailing_term
-                        {
-                            info!("Send channel for crawling: {}", sub_channel_id);
+        info!(
+            "Start discovery run {} with {} seeds",
+            run.run_id,
+            run.seeds.len()
+        );
+
+        self.settings_repo.set_discovery_run(&run).await?;
+
+        Ok(run)
+    }
 
-                            let cmd = CrawlChannelCommand {
-                                channel_id: sub_channel_id.clone(),
-                                ignore_sailing_terms: false,
-                            };
+    /// Processes the remaining seeds of a run and stores a checkpoint after every seed,
+    /// so that a restarted process continues with the next unprocessed seed. A run
+    /// that runs out of quota budget is kept together with the expansion state of its
+    /// current seed and continued in the next cycle.
+    async fn run_discovery(&self, mut run: DiscoveryRun) -> Result<(), Error> {
+        let quota_at_start = self.youtube_service.used_quota() - run.used_quota;
 
-                            self.sender.send(cmd).await?;
+        while run.cursor < run.seeds.len() {
+            if self.is_quota_budget_exhausted(quota_at_start) {
+                info!("Discovery quota budget exhausted");
+                return self.pause_discovery_run(run).await;
+            }
+
+            if !self.discover_from_seed(&mut run, quota_at_start).await? {
+                return self.pause_discovery_run(run).await;
+            }
+
+            run.seed_progress = None;
+            run.cursor += 1;
+            run.used_quota = self.youtube_service.used_quota() - quota_at_start;
+            self.settings_repo.set_discovery_run(&run).await?;
+        }
+
+        info!(
+            "Discovery run {} used {} of {} seeds ({} private, {} failed, {} skipped)",
+            run.run_id,
+            run.usable_seeds,
+            run.cursor,
+            run.private_seeds,
+            run.failed_seeds,
+            run.skipped_seeds
+        );
+
+        if self.is_quota_budget_exhausted(quota_at_start) {
+            info!("Discovery quota budget exhausted, skip mention and search discovery");
+        } else {
+            self.discover_from_mentions(&run.run_id).await?;
+            self.discover_from_search(&run.run_id).await?;
+        }
+
+        self.settings_repo.finish_discovery_run(&run).await?;
+
+        Ok(())
+    }
+
+    /// Keeps the checkpoint of an unfinished run with a fresh quota budget, so that
+    /// the next cycle continues at the current seed.
+    async fn pause_discovery_run(&self, mut run: DiscoveryRun) -> Result<(), Error> {
+        info!(
+            "Pause discovery run {} at seed {} of {}",
+            run.run_id,
+            run.cursor,
+            run.seeds.len()
+        );
+
+        run.used_quota = 0;
+        self.settings_repo.set_discovery_run(&run).await
+    }
+
+    /// Expands the subscriptions and featured channels of a seed channel breadth first.
+    /// Channels accepted on one hop are expanded on the next one, as long as the
+    /// decayed score stays above the configured minimum. The subscriptions status of
+    /// the seed channel itself is counted in the run. Returns `false` when the quota
+    /// runs out, with the expansion state kept in the run, so that the next cycle
+    /// continues with the remaining channels of the seed.
+    async fn discover_from_seed(
+        &self,
+        run: &mut DiscoveryRun,
+        quota_at_start: i64,
+    ) -> Result<bool, Error> {
+        let seed_channel_id = run.seeds[run.cursor].clone();
+        let mut progress = run.seed_progress.take().unwrap_or_else(|| SeedProgress {
+            depth: 1,
+            frontier: vec![FrontierChannel {
+                channel_id: seed_channel_id.clone(),
+                score: 1.0,
+                featured_channel_ids: None,
+            }],
+            next_frontier: vec![],
+            visited: HashSet::from([seed_channel_id.clone()]),
+        });
+
+        while progress.depth <= self.config.max_depth {
+            while let Some(channel) = progress.frontier.first().cloned() {
+                if self.is_quota_budget_exhausted(quota_at_start) {
+                    run.seed_progress = Some(progress);
+                    return Ok(false);
+                }
+
+                let accepted_channels = match self
+                    .expand_channel(&channel, progress.depth, &run.run_id)
+                    .await
+                {
+                    Ok((status, accepted_channels)) => {
+                        if progress.depth == 1 {
+                            match status {
+                                SubscriptionsStatus::Usable => run.usable_seeds += 1,
+                                SubscriptionsStatus::Private => run.private_seeds += 1,
+                                SubscriptionsStatus::Failed => run.failed_seeds += 1,
+                                SubscriptionsStatus::Skipped => run.skipped_seeds += 1,
+                            }
                         }
+
+                        accepted_channels
+                    }
+                    Err(e) if is_quota_exceeded(&e) => {
+                        warn!("Quota exceeded while checking seed {}", seed_channel_id);
+                        run.seed_progress = Some(progress);
+                        return Ok(false);
+                    }
+                    Err(e) => return Err(e),
+                };
+
+                progress.frontier.remove(0);
+
+                let next_score = channel.score * self.config.depth_decay;
+                if next_score < self.config.min_score {
+                    continue;
+                }
+
+                for accepted_channel in accepted_channels {
+                    if progress.visited.insert(accepted_channel.channel_id.clone()) {
+                        progress.next_frontier.push(FrontierChannel {
+                            channel_id: accepted_channel.channel_id,
+                            score: next_score,
+                            featured_channel_ids: accepted_channel.featured_channel_ids,
+                        });
                     }
                 }
+            }
 
-                let crawl_timestamp = Utc::now().timestamp();
-                self.settings_repo
-                    .set_last_discovery_crawl(crawl_timestamp)
-                    .await;
+            if progress.next_frontier.is_empty() {
+                break;
             }
 
-            info!("Wait for {} seconds until next crawl", ONE_DAYS_IN_SECONDS);
+            progress.frontier = std::mem::take(&mut progress.next_frontier);
+            progress.depth += 1;
+        }
 
-            sleep(Duration::from_secs(ONE_DAYS_IN_SECONDS)).await;
+        Ok(true)
+    }
+
+    /// Checks the subscriptions and featured channels of a channel. Returns the
+    /// subscriptions status of the channel and the accepted channels.
+    async fn expand_channel(
+        &self,
+        channel: &FrontierChannel,
+        depth: u32,
+        run_id: &str,
+    ) -> Result<(SubscriptionsStatus, Vec<AcceptedChannel>), Error> {
+        let (status, mut accepted_channels) = self
+            .discover_from_subscriptions(&channel.channel_id, depth, run_id)
+            .await?;
+
+        accepted_channels.extend(
+            self.discover_from_featured_channels(
+                &channel.channel_id,
+                channel.featured_channel_ids.clone(),
+                depth,
+                run_id,
+            )
+            .await?,
+        );
+
+        Ok((status, accepted_channels))
+    }
+
+    fn is_quota_budget_exhausted(&self, quota_at_start: i64) -> bool {
+        self.config.quota_budget > 0
+            && self.youtube_service.used_quota() - quota_at_start >= self.config.quota_budget
+    }
+
+    async fn discover_from_subscriptions(
+        &self,
+        channel_id: &str,
+        depth: u32,
+        run_id: &str,
+    ) -> Result<(SubscriptionsStatus, Vec<AcceptedChannel>), Error> {
+        info!("Check subscriptions of channel {}", channel_id);
+
+        let seed = self.discovery_seed_repo.get(channel_id).await?;
+        let now = Utc::now().timestamp();
+
+        if let Some(next_attempt_at) = seed.as_ref().and_then(|seed| seed.next_attempt_at) {
+            if next_attempt_at > now {
+                info!(
+                    "Skip subscriptions of channel {} until {}",
+                    channel_id, next_attempt_at
+                );
+                return Ok((SubscriptionsStatus::Skipped, vec![]));
+            }
+        }
+
+        let known_etag = seed
+            .as_ref()
+            .and_then(|seed| seed.subscriptions_etag.clone());
+
+        let subscriptions = match self
+            .youtube_service
+            .get_channel_subscriptions(channel_id, known_etag.as_deref())
+            .await
+        {
+            Ok(Some(subscriptions)) => subscriptions,
+            Ok(None) => {
+                info!("Subscriptions of channel {} are unchanged", channel_id);
+
+                self.channel_edge_repo
+                    .touch_all_from(channel_id, EdgeSource::Subscription)
+                    .await?;
+                self.discovery_seed_repo
+                    .set_subscriptions_etag(channel_id, known_etag.as_deref())
+                    .await?;
+
+                let accepted_channels = self.get_known_subscriptions(channel_id).await?;
+
+                return Ok((SubscriptionsStatus::Usable, accepted_channels));
+            }
+            Err(e) if is_quota_exceeded(&e) => return Err(e),
+            Err(e) if e.downcast_ref::<reqwest::Error>().is_some() => {
+                warn!(
+                    "Failed to load subscriptions of channel {}: {}",
+                    channel_id, e
+                );
+
+                return Ok((SubscriptionsStatus::Skipped, vec![]));
+            }
+            Err(e) => {
+                let status = match e.downcast_ref::<YoutubeApiError>() {
+                    Some(api_error) if api_error.has_reason(SUBSCRIPTION_FORBIDDEN) => {
+                        SubscriptionsStatus::Private
+                    }
+                    _ => SubscriptionsStatus::Failed,
+                };
+
+                let failures = seed.map(|seed| seed.failures).unwrap_or(0) + 1;
+                let next_attempt_at = now + get_backoff_seconds(failures, MAX_BACKOFF_DAYS);
+
+                warn!(
+                    "Subscriptions of channel {} are {}, retry after {}: {}",
+                    channel_id,
+                    status.as_str(),
+                    next_attempt_at,
+                    e
+                );
+
+                self.discovery_seed_repo
+                    .set_subscriptions_failure(channel_id, status, failures, next_attempt_at)
+                    .await?;
+
+                return Ok((status, vec![]));
+            }
+        };
+
+        for snippet in &subscriptions.snippets {
+            self.channel_edge_repo
+                .upsert(
+                    channel_id,
+                    &snippet.resource_id.channel_id,
+                    EdgeSource::Subscription,
+                )
+                .await?;
+        }
+
+        self.discovery_seed_repo
+            .set_subscriptions_etag(channel_id, subscriptions.etag.as_deref())
+            .await?;
+
+        let mut accepted_channels = vec![];
+
+        for snippet in subscriptions.snippets {
+            let discovery = Discovery {
+                source: DiscoverySource::Subscription {
+                    channel_id: channel_id.to_string(),
+                },
+                depth: Some(depth),
+                run_id: Some(run_id.to_string()),
+            };
+
+            let accepted = self
+                .check_discovered_channel(
+                    &snippet.resource_id.channel_id,
+                    &snippet.title,
+                    &snippet.description,
+                    discovery,
+                )
+                .await?;
+
+            if accepted {
+                accepted_channels.push(AcceptedChannel {
+                    channel_id: snippet.resource_id.channel_id,
+                    featured_channel_ids: None,
+                });
+            }
+        }
+
+        Ok((SubscriptionsStatus::Usable, accepted_channels))
+    }
+
+    /// Subscriptions of an unchanged list that are stored as channels, so that the
+    /// expansion continues through them without loading the list again.
+    async fn get_known_subscriptions(
+        &self,
+        channel_id: &str,
+    ) -> Result<Vec<AcceptedChannel>, Error> {
+        let mut accepted_channels = vec![];
+
+        for subscription_id in self
+            .channel_edge_repo
+            .get_targets_from(channel_id, EdgeSource::Subscription)
+            .await?
+        {
+            if self.channel_repo.exists(&subscription_id).await? {
+                accepted_channels.push(AcceptedChannel {
+                    channel_id: subscription_id,
+                    featured_channel_ids: None,
+                });
+            }
+        }
+
+        Ok(accepted_channels)
+    }
+
+    /// Checks the channels of the channel sections and the featured channels of the
+    /// branding settings. The branding settings are only loaded when they are not
+    /// known yet. A failing source is skipped, unless the quota is exceeded.
+    async fn discover_from_featured_channels(
+        &self,
+        channel_id: &str,
+        featured_channels_urls: Option<Vec<String>>,
+        depth: u32,
+        run_id: &str,
+    ) -> Result<Vec<AcceptedChannel>, Error> {
+        info!("Check featured channels of channel {}", channel_id);
+
+        let section_channel_ids = self
+            .youtube_service
+            .get_channel_section_channel_ids(channel_id)
+            .await;
+        let featured_channels_urls = match featured_channels_urls {
+            Some(featured_channels_urls) => Ok(featured_channels_urls),
+            None => {
+                self.youtube_service
+                    .get_featured_channels_urls(channel_id)
+                    .await
+            }
+        };
+
+        let mut featured_channel_ids = vec![];
+
+        for result in [section_channel_ids, featured_channels_urls] {
+            match result {
+                Ok(channel_ids) => featured_channel_ids.extend(channel_ids),
+                Err(e) if is_quota_exceeded(&e) => return Err(e),
+                Err(e) => warn!(
+                    "Failed to load featured channels of channel {}: {}",
+                    channel_id, e
+                ),
+            }
+        }
+
+        featured_channel_ids.retain(|id| id != channel_id);
+        featured_channel_ids.sort();
+        featured_channel_ids.dedup();
+
+        for featured_channel_id in &featured_channel_ids {
+            self.channel_edge_repo
+                .upsert(channel_id, featured_channel_id, EdgeSource::FeaturedChannel)
+                .await?;
+        }
+
+        let discovery = Discovery {
+            source: DiscoverySource::FeaturedChannel {
+                channel_id: channel_id.to_string(),
+            },
+            depth: Some(depth),
+            run_id: Some(run_id.to_string()),
+        };
+
+        let candidates = featured_channel_ids
+            .into_iter()
+            .map(|featured_channel_id| (featured_channel_id, discovery.clone()))
+            .collect();
+
+        self.check_discovered_channel_ids(candidates).await
+    }
+
+    /// Resolves channels mentioned in video descriptions updated since the last run.
+    /// Descriptions are loaded page by page and the checkpoint is stored after every
+    /// page. Once the quota budget is used up, the remaining descriptions are left for
+    /// the next run.
+    async fn discover_from_mentions(&self, run_id: &str) -> Result<(), Error> {
+        let mut checkpoint = self
+            .settings_repo
+            .get_last_mention_discovery_crawl()
+            .await?;
+        let mut updated_after = checkpoint;
+        let mut after_id: Option<String> = None;
+
+        let quota_at_start = self.youtube_service.used_quota();
+        let mut checked_references = HashSet::new();
+        let mut resolved: HashMap<ChannelReference, Option<String>> = HashMap::new();
+
+        loop {
+            let descriptions = self
+                .video_repo
+                .get_descriptions_updated_after(
+                    updated_after,
+                    after_id.as_deref(),
+                    MENTION_PAGE_SIZE,
+                )
+                .await?;
+            let is_last_page = descriptions.len() < MENTION_PAGE_SIZE as usize;
+            let mut is_budget_exhausted = false;
+            let mut candidates: HashMap<String, Discovery> = HashMap::new();
+
+            info!(
+                "Check channel mentions in {} video descriptions",
+                descriptions.len()
+            );
+
+            for video in descriptions {
+                if self.youtube_service.used_quota() - quota_at_start
+                    >= self.config.mention_quota_budget
+                {
+                    info!("Mention discovery quota budget exhausted");
+                    is_budget_exhausted = true;
+                    break;
+                }
+
+                for reference in parse_channel_references(&video.description) {
+                    if !checked_references.insert((video.channel.clone(), reference.clone())) {
+                        continue;
+                    }
+
+                    // Failed resolutions are not cached and stop the run before the
+                    // checkpoint of this page is stored, so they are retried later.
+                    if !resolved.contains_key(&reference) {
+                        let mentioned_channel_id = self
+                            .youtube_service
+                            .resolve_channel_reference(&reference)
+                            .await?;
+
+                        resolved.insert(reference.clone(), mentioned_channel_id);
+                    }
+
+                    if let Some(mentioned_channel_id) = &resolved[&reference] {
+                        if mentioned_channel_id != &video.channel {
+                            self.channel_edge_repo
+                                .upsert(&video.channel, mentioned_channel_id, EdgeSource::Mention)
+                                .await?;
+
+                            candidates
+                                .entry(mentioned_channel_id.clone())
+                                .or_insert_with(|| Discovery {
+                                    source: DiscoverySource::Mention {
+                                        channel_id: video.channel.clone(),
+                                    },
+                                    depth: None,
+                                    run_id: Some(run_id.to_string()),
+                                });
+                        }
+                    }
+                }
+
+                // Videos updated in the same second are checked again in the next run.
+                checkpoint = checkpoint.max(video.updated_at - 1);
+                updated_after = video.updated_at;
+                after_id = Some(video.id);
+            }
+
+            self.check_discovered_channel_ids(candidates.into_iter().collect())
+                .await?;
+
+            self.settings_repo
+                .set_last_mention_discovery_crawl(checkpoint)
+                .await;
+
+            if is_budget_exhausted || is_last_page {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Searches for channels and recent videos matching the sailing terms. Every run
+    /// continues with the next term and language combination until the quota budget
+    /// is used up or both searches of a combination fail.
+    async fn discover_from_search(&self, run_id: &str) -> Result<(), Error> {
+        let terms = self.sailing_terms_service.sailing_terms();
+        let languages = &self.config.search_languages;
+        let combinations = (terms.len() * languages.len()) as i64;
+
+        if self.config.search_quota_budget < SEARCH_QUOTA_COST as i64 || combinations == 0 {
+            return Ok(());
+        }
+
+        let quota_at_start = self.youtube_service.used_quota();
+        let published_after = Utc::now() - chrono::Duration::weeks(4);
+        let mut cursor = self.settings_repo.get_search_discovery_cursor().await?;
+        let mut searched = 0;
+
+        while searched < combinations
+            && self.youtube_service.used_quota() - quota_at_start + 2 * SEARCH_QUOTA_COST as i64
+                <= self.config.search_quota_budget
+        {
+            let index = (cursor % combinations) as usize;
+            let term = &terms[index / languages.len()];
+            let language = &languages[index % languages.len()];
+
+            info!("Search for channels matching '{}' ({})", term, language);
+
+            let mut channel_ids = vec![];
+            let mut has_succeeded = false;
+
+            match self
+                .youtube_service
+                .search_channel_ids(term, language, "channel", None)
+                .await
+            {
+                Ok(ids) => {
+                    channel_ids.extend(ids);
+                    has_succeeded = true;
+                }
+                Err(e) => warn!("Failed to search channels for '{}': {}", term, e),
+            }
+
+            match self
+                .youtube_service
+                .search_channel_ids(term, language, "video", Some(published_after))
+                .await
+            {
+                Ok(ids) => {
+                    channel_ids.extend(ids);
+                    has_succeeded = true;
+                }
+                Err(e) => warn!("Failed to search videos for '{}': {}", term, e),
+            }
+
+            // The same combination is searched again in the next run.
+            if !has_succeeded {
+                break;
+            }
+
+            channel_ids.sort();
+            channel_ids.dedup();
+
+            let discovery = Discovery {
+                source: DiscoverySource::Search {
+                    term: term.to_string(),
+                },
+                depth: None,
+                run_id: Some(run_id.to_string()),
+            };
+
+            let candidates = channel_ids
+                .into_iter()
+                .map(|channel_id| (channel_id, discovery.clone()))
+                .collect();
+
+            self.check_discovered_channel_ids(candidates).await?;
+
+            cursor = (cursor + 1) % combinations;
+            searched += 1;
+
+            self.settings_repo.set_search_discovery_cursor(cursor).await;
         }
+
+        Ok(())
+    }
+
+    /// Checks channels of which only the id is known. Details are only loaded for
+    /// channels that are not yet known, to keep the quota usage low.
+    async fn check_discovered_channel_ids(
+        &self,
+        candidates: Vec<(String, Discovery)>,
+    ) -> Result<Vec<AcceptedChannel>, Error> {
+        let mut unknown_channels: HashMap<String, Discovery> = HashMap::new();
+
+        for (channel_id, discovery) in candidates {
+            let is_newly_discovered = self.is_channel_newly_discovered(&channel_id).await?;
+
+            let is_not_non_sailing_channel = self
+                .sailing_terms_service
+                .is_not_listed_as_non_sailing_channel(&channel_id)
+                .await;
+
+            if is_newly_discovered && is_not_non_sailing_channel {
+                unknown_channels.insert(channel_id, discovery);
+            }
+        }
+
+        if unknown_channels.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let unknown_channel_ids = unknown_channels.keys().cloned().collect::<Vec<String>>();
+
+        let channels_details = self
+            .youtube_service
+            .get_channels_details(&unknown_channel_ids)
+            .await?;
+
+        let mut accepted_channels = vec![];
+
+        for channel_details in channels_details {
+            let discovery = match unknown_channels.remove(&channel_details.id) {
+                Some(discovery) => discovery,
+                None => continue,
+            };
+
+            let accepted = self
+                .check_discovered_channel(
+                    &channel_details.id,
+                    &channel_details.snippet.title,
+                    &channel_details.snippet.description.unwrap_or_default(),
+                    discovery,
+                )
+                .await?;
+
+            if accepted {
+                accepted_channels.push(AcceptedChannel {
+                    channel_id: channel_details.id,
+                    featured_channel_ids: Some(
+                        channel_details
+                            .branding_settings
+                            .channel
+                            .featured_channels_urls
+                            .unwrap_or_default(),
+                    ),
+                });
+            }
+        }
+
+        Ok(accepted_channels)
+    }
+
+    async fn check_discovered_channel(
+        &self,
+        channel_id: &str,
+        title: &str,
+        description: &str,
+        discovery: Discovery,
+    ) -> Result<bool, Error> {
+        let sailing_terms_result = self
+            .sailing_terms_service
+            .has_sailing_term(channel_id, title, description, false)
+            .await;
+
+        let is_newly_discovered = self.is_channel_newly_discovered(channel_id).await?;
+
+        let is_not_non_sailing_channel = self
+            .sailing_terms_service
+            .is_not_listed_as_non_sailing_channel(channel_id)
+            .await;
+
+        let accepted = is_newly_discovered
+            && is_not_non_sailing_channel
+            && sailing_terms_result.has_sailing_term;
+
+        if accepted {
+            info!("Send channel for crawling: {}", channel_id);
+
+            let cmd = CrawlChannelCommand {
+                channel_id: channel_id.to_string(),
+                ignore_sailing_terms: false,
+                discovery: Some(discovery),
+                submission_id: None,
+                priority: CrawlPriority::Discovery,
+            };
+
+            self.sender.send(cmd).await?;
+        }
+
+        Ok(accepted)
     }
 
     async fn should_crawl(&self) -> Result<bool, Error> {
@@ -124,3 +844,10 @@ impl ChannelDiscoveryCrawler {
         Ok(!channel_exists && !additional_exists)
     }
 }
+
+/// Running out of quota is not the fault of a seed, so it pauses the whole run
+/// instead of backing off the seed.
+fn is_quota_exceeded(err: &Error) -> bool {
+    err.downcast_ref::<YoutubeApiError>()
+        .is_some_and(|api_error| api_error.has_reason(QUOTA_EXCEEDED))
+}
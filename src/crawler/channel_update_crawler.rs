@@ -6,7 +6,9 @@ use tokio::sync::mpsc::Sender;
 use tokio::time::sleep;
 
 use crate::{
-    commands::crawl_channel_command::CrawlChannelCommand, repos::channel_repo::ChannelRepository,
+    commands::crawl_channel_command::{CrawlChannelCommand, CrawlPriority},
+    repos::channel_repo::ChannelRepository,
+    utils::consts::DORMANT_AFTER_WEEKS,
 };
 
 const FIFTEEN_MINUTES_IN_SECONDS: u64 = 15 * 60;
@@ -32,7 +34,7 @@ impl ChannelUpdateCrawler {
             info!("Start channel update crawler");
 
             let last_crawl_before = Utc::now() - chrono::Duration::days(1);
-            let last_upload_after = Utc::now() - chrono::Duration::weeks(52);
+            let last_upload_after = Utc::now() - chrono::Duration::weeks(DORMANT_AFTER_WEEKS);
             let channel_ids = self
                 .channel_repo
                 .get_ids_last_crawled_before(last_crawl_before, last_upload_after)
@@ -44,6 +46,9 @@ impl ChannelUpdateCrawler {
                 let cmd = CrawlChannelCommand {
                     channel_id,
                     ignore_sailing_terms: false,
+                    discovery: None,
+                    submission_id: None,
+                    priority: CrawlPriority::Update,
                 };
 
                 self.sender.send(cmd).await?;
@@ -0,0 +1,70 @@
+use anyhow::Error;
+use chrono::Utc;
+use log::info;
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+use tokio::time::sleep;
+
+use crate::{
+    commands::crawl_videos_command::CrawlVideosCommand,
+    repos::channel_repo::ChannelRepository,
+    utils::consts::{DORMANT_AFTER_WEEKS, ONE_DAYS_IN_SECONDS},
+};
+
+/// Checks the video feeds of dormant channels once a week, which costs no API quota.
+/// The video scraper marks a channel as revived once it finds a recent upload, and
+/// the next sweep marks it as active again.
+pub struct DormantChannelCrawler {
+    sender: Sender<CrawlVideosCommand>,
+    channel_repo: ChannelRepository,
+}
+
+impl DormantChannelCrawler {
+    pub fn new(
+        sender: Sender<CrawlVideosCommand>,
+        channel_repo: ChannelRepository,
+    ) -> DormantChannelCrawler {
+        DormantChannelCrawler {
+            sender,
+            channel_repo,
+        }
+    }
+
+    pub async fn crawl(&self) -> Result<(), Error> {
+        loop {
+            info!("Start dormant channel crawler");
+
+            // Channels stay revived for one sweep, after that they count as active.
+            let active_count = self.channel_repo.activate_revived().await?;
+
+            info!("Marked {} revived channels as active", active_count);
+
+            let last_upload_before = Utc::now() - chrono::Duration::weeks(DORMANT_AFTER_WEEKS);
+            let dormant_count = self
+                .channel_repo
+                .update_dormant_status(last_upload_before)
+                .await?;
+
+            info!("Marked {} channels as dormant", dormant_count);
+
+            let channel_ids = self.channel_repo.get_dormant_ids().await?;
+
+            info!("Check feeds of {} dormant channels", channel_ids.len());
+
+            for channel_id in channel_ids {
+                let command = CrawlVideosCommand {
+                    channel_id,
+                    backfill: false,
+                };
+
+                self.sender.send(command).await?;
+            }
+
+            let wait_seconds = 7 * ONE_DAYS_IN_SECONDS;
+
+            info!("Wait for {} seconds until next crawl", wait_seconds);
+
+            sleep(Duration::from_secs(wait_seconds)).await;
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use anyhow::Error;
+use log::info;
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::{
+    repos::{channel_edge_repo::ChannelEdgeRepository, channel_repo::ChannelRepository},
+    utils::{consts::ONE_DAYS_IN_SECONDS, graph_utils::page_rank},
+};
+
+const DAMPING_FACTOR: f64 = 0.85;
+const ITERATIONS: usize = 50;
+
+pub struct InfluenceCrawler {
+    channel_repo: ChannelRepository,
+    channel_edge_repo: ChannelEdgeRepository,
+}
+
+impl InfluenceCrawler {
+    pub fn new(
+        channel_repo: ChannelRepository,
+        channel_edge_repo: ChannelEdgeRepository,
+    ) -> InfluenceCrawler {
+        InfluenceCrawler {
+            channel_repo,
+            channel_edge_repo,
+        }
+    }
+
+    pub async fn crawl(&self) -> Result<(), Error> {
+        loop {
+            info!("Start influence crawler");
+
+            let channel_ids = self.channel_repo.get_all_ids().await?;
+            let edges = self.channel_edge_repo.get_all().await?;
+
+            info!(
+                "Compute influence of {} channels over {} edges",
+                channel_ids.len(),
+                edges.len()
+            );
+
+            let scores = page_rank(&channel_ids, &edges, DAMPING_FACTOR, ITERATIONS);
+
+            for (channel_id, score) in scores {
+                self.channel_repo.set_influence(&channel_id, score).await?;
+            }
+
+            info!("Wait for {} seconds until next crawl", ONE_DAYS_IN_SECONDS);
+
+            sleep(Duration::from_secs(ONE_DAYS_IN_SECONDS)).await;
+        }
+    }
+}
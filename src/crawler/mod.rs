@@ -1,4 +1,8 @@
 pub mod additional_channel_crawler;
 pub mod channel_discovery_crawler;
 pub mod channel_update_crawler;
+pub mod dormant_channel_crawler;
+pub mod influence_crawler;
 pub mod new_video_crawler;
+pub mod terminated_channel_crawler;
+pub mod video_enrichment_crawler;
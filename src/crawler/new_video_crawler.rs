@@ -4,25 +4,29 @@ use std::time::Duration;
 use tokio::sync::mpsc::Sender;
 use tokio::time::sleep;
 
-const SIXTY_MINUTES_IN_SECONDS: u64 = 60 * 60;
-
 use crate::{
-    commands::crawl_videos_command::CrawlVideosCommand, repos::channel_repo::ChannelRepository,
+    commands::crawl_videos_command::CrawlVideosCommand, models::config::VideoConfig,
+    repos::channel_repo::ChannelRepository,
 };
 
+const ONE_HOUR_IN_SECONDS: u64 = 60 * 60;
+
 pub struct NewVideoCrawler {
     sender: Sender<CrawlVideosCommand>,
     channel_repo: ChannelRepository,
+    config: VideoConfig,
 }
 
 impl NewVideoCrawler {
     pub fn new(
         sender: Sender<CrawlVideosCommand>,
         channel_repo: ChannelRepository,
+        config: VideoConfig,
     ) -> NewVideoCrawler {
         NewVideoCrawler {
             sender,
             channel_repo,
+            config,
         }
     }
 
@@ -30,22 +34,24 @@ impl NewVideoCrawler {
         loop {
             info!("Start new video crawler");
 
-            let channels = self.channel_repo.get_all_ids().await?;
+            let channels = self.channel_repo.get_ids_video_poll_due().await?;
+
+            info!("Found {} channels due for video polling", channels.len());
 
             for channel in channels {
                 let command = CrawlVideosCommand {
                     channel_id: channel.clone(),
+                    backfill: false,
                 };
 
                 self.sender.send(command).await?;
             }
 
-            info!(
-                "Wait for {} seconds until next crawl",
-                SIXTY_MINUTES_IN_SECONDS
-            );
+            let wait_seconds = self.config.min_poll_interval_hours as u64 * ONE_HOUR_IN_SECONDS;
+
+            info!("Wait for {} seconds until next crawl", wait_seconds);
 
-            sleep(Duration::from_secs(SIXTY_MINUTES_IN_SECONDS)).await;
+            sleep(Duration::from_secs(wait_seconds)).await;
         }
     }
 }
@@ -0,0 +1,89 @@
+use anyhow::Error;
+use chrono::Utc;
+use log::info;
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::{
+    repos::{
+        channel_edge_repo::ChannelEdgeRepository, channel_repo::ChannelRepository,
+        discovery_seed_repo::DiscoverySeedRepository, subscriber_repo::SubscriberRepository,
+        video_history_repo::VideoHistoryRepository, video_repo::VideoRepository,
+        video_view_repo::VideoViewRepository, view_repo::ViewRepository,
+    },
+    utils::consts::ONE_DAYS_IN_SECONDS,
+};
+
+/// Deletes channels, including their videos, statistics and discovery data, once
+/// they have been terminated for longer than the grace period.
+pub struct TerminatedChannelCrawler {
+    channel_repo: ChannelRepository,
+    view_repo: ViewRepository,
+    subscriber_repo: SubscriberRepository,
+    video_repo: VideoRepository,
+    video_view_repo: VideoViewRepository,
+    video_history_repo: VideoHistoryRepository,
+    channel_edge_repo: ChannelEdgeRepository,
+    discovery_seed_repo: DiscoverySeedRepository,
+    purge_after_days: i64,
+}
+
+impl TerminatedChannelCrawler {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        channel_repo: ChannelRepository,
+        view_repo: ViewRepository,
+        subscriber_repo: SubscriberRepository,
+        video_repo: VideoRepository,
+        video_view_repo: VideoViewRepository,
+        video_history_repo: VideoHistoryRepository,
+        channel_edge_repo: ChannelEdgeRepository,
+        discovery_seed_repo: DiscoverySeedRepository,
+        purge_after_days: i64,
+    ) -> TerminatedChannelCrawler {
+        TerminatedChannelCrawler {
+            channel_repo,
+            view_repo,
+            subscriber_repo,
+            video_repo,
+            video_view_repo,
+            video_history_repo,
+            channel_edge_repo,
+            discovery_seed_repo,
+            purge_after_days,
+        }
+    }
+
+    pub async fn crawl(&self) -> Result<(), Error> {
+        loop {
+            info!("Start terminated channel crawler");
+
+            let terminated_before = Utc::now() - chrono::Duration::days(self.purge_after_days);
+            let channel_ids = self
+                .channel_repo
+                .get_ids_terminated_before(terminated_before)
+                .await?;
+
+            info!("Purge {} terminated channels", channel_ids.len());
+
+            for channel_id in channel_ids {
+                self.channel_repo.delete(&channel_id).await?;
+                self.view_repo.delete_by_channel(&channel_id).await?;
+                self.subscriber_repo.delete_by_channel(&channel_id).await?;
+                self.video_repo.delete_all_by_channel(&channel_id).await?;
+                self.video_view_repo.delete_by_channel(&channel_id).await?;
+                self.video_history_repo
+                    .delete_by_channel(&channel_id)
+                    .await?;
+                self.channel_edge_repo
+                    .delete_by_channel(&channel_id)
+                    .await?;
+                self.discovery_seed_repo.delete(&channel_id).await?;
+            }
+
+            info!("Wait for {} seconds until next crawl", ONE_DAYS_IN_SECONDS);
+
+            sleep(Duration::from_secs(ONE_DAYS_IN_SECONDS)).await;
+        }
+    }
+}
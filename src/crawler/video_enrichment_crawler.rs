@@ -0,0 +1,244 @@
+use anyhow::Error;
+use chrono::Utc;
+use log::{error, info, warn};
+use mongodb::bson::{doc, Document};
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::time::sleep;
+
+use crate::{
+    models::{config::VideoConfig, youtube_video_details::YoutubeVideoItem},
+    repos::{channel_repo::ChannelRepository, video_repo::VideoRepository},
+    services::youtube_service::{YoutubeService, MAX_IDS_PER_REQUEST},
+    utils::{
+        consts::ONE_DAYS_IN_SECONDS,
+        duration_utils::parse_iso8601_duration,
+        video_format_utils::{classify_video_format, VideoFormat, MAX_SHORT_DURATION_SECONDS},
+    },
+};
+
+const REFRESH_AFTER_DAYS: i64 = 7;
+
+/// Adds statistics and metadata from the videos API to the videos found in the
+/// feeds. Every request covers 50 videos and costs one quota unit. Videos missing
+/// from the response were deleted or made private and are marked as removed.
+pub struct VideoEnrichmentCrawler {
+    video_repo: VideoRepository,
+    channel_repo: ChannelRepository,
+    youtube_service: YoutubeService,
+    config: VideoConfig,
+}
+
+impl VideoEnrichmentCrawler {
+    pub fn new(
+        video_repo: VideoRepository,
+        channel_repo: ChannelRepository,
+        youtube_service: YoutubeService,
+        config: VideoConfig,
+    ) -> VideoEnrichmentCrawler {
+        VideoEnrichmentCrawler {
+            video_repo,
+            channel_repo,
+            youtube_service,
+            config,
+        }
+    }
+
+    pub async fn crawl(&self) -> Result<(), Error> {
+        loop {
+            info!("Start video enrichment crawler");
+
+            if let Err(e) = self.enrich_videos().await {
+                error!("Failed to enrich videos: {}", e);
+            }
+
+            info!("Wait for {} seconds until next crawl", ONE_DAYS_IN_SECONDS);
+
+            sleep(Duration::from_secs(ONE_DAYS_IN_SECONDS)).await;
+        }
+    }
+
+    async fn enrich_videos(&self) -> Result<(), Error> {
+        let enriched_before = Utc::now() - chrono::Duration::days(REFRESH_AFTER_DAYS);
+        let video_ids = self
+            .video_repo
+            .get_ids_for_enrichment(
+                enriched_before,
+                self.config.enrichment_quota_budget * MAX_IDS_PER_REQUEST as i64,
+            )
+            .await?;
+
+        info!("Found {} videos to enrich", video_ids.len());
+
+        let quota_at_start = self.youtube_service.used_quota();
+        let mut enriched_count = 0;
+
+        for chunk in video_ids.chunks(MAX_IDS_PER_REQUEST) {
+            if self.youtube_service.used_quota() - quota_at_start
+                >= self.config.enrichment_quota_budget
+            {
+                info!("Video enrichment quota budget is exhausted");
+                break;
+            }
+
+            let items = match self.youtube_service.get_videos_details(chunk).await {
+                Ok(items) => items,
+                Err(e) => {
+                    warn!("Failed to load video details, stop enrichment: {}", e);
+                    break;
+                }
+            };
+
+            let returned_ids = items
+                .iter()
+                .map(|item| item.id.as_str())
+                .collect::<HashSet<&str>>();
+            let removed_ids = chunk
+                .iter()
+                .filter(|id| !returned_ids.contains(id.as_str()))
+                .cloned()
+                .collect::<Vec<String>>();
+
+            if !removed_ids.is_empty() {
+                if let Err(e) = self.mark_removed(&removed_ids).await {
+                    warn!("Failed to mark videos as removed: {}", e);
+                }
+            }
+
+            let premiere_ids = self
+                .video_repo
+                .get_ids_with_format(chunk, VideoFormat::Premiere.as_str())
+                .await?;
+
+            for item in items {
+                let mut details = build_details_document(&item);
+                let was_premiere = premiere_ids.contains(&item.id);
+
+                match self.get_video_format(&item, was_premiere).await {
+                    Ok(format) => {
+                        details.insert("format", format.as_str());
+                    }
+                    Err(e) => warn!("Failed to classify video {}: {}", item.id, e),
+                }
+
+                self.video_repo.set_details(&item.id, details).await?;
+
+                enriched_count += 1;
+            }
+        }
+
+        info!("Enriched {} videos", enriched_count);
+
+        Ok(())
+    }
+
+    async fn mark_removed(&self, video_ids: &[String]) -> Result<(), Error> {
+        info!("Mark {} videos as removed", video_ids.len());
+
+        let channel_ids = self.video_repo.set_removed(video_ids).await?;
+
+        for channel_id in channel_ids {
+            let video_count = self.video_repo.count(&channel_id).await?;
+
+            self.channel_repo
+                .set_video_count(&channel_id, video_count as i64)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_video_format(
+        &self,
+        item: &YoutubeVideoItem,
+        was_premiere: bool,
+    ) -> Result<VideoFormat, Error> {
+        let duration = item
+            .content_details
+            .as_ref()
+            .and_then(|content_details| content_details.duration.as_deref())
+            .and_then(parse_iso8601_duration);
+        let has_live_streaming_details = item.live_streaming_details.is_some();
+
+        let is_short = match duration {
+            Some(duration)
+                if !has_live_streaming_details
+                    && duration > 0
+                    && duration <= MAX_SHORT_DURATION_SECONDS =>
+            {
+                self.youtube_service.is_short(&item.id).await?
+            }
+            _ => false,
+        };
+
+        Ok(classify_video_format(
+            duration,
+            item.snippet.live_broadcast_content.as_deref(),
+            has_live_streaming_details,
+            is_short,
+            was_premiere,
+        ))
+    }
+}
+
+fn build_details_document(item: &YoutubeVideoItem) -> Document {
+    let mut details = doc! {
+        "enrichedAt": mongodb::bson::DateTime::now(),
+    };
+
+    if let Some(statistics) = &item.statistics {
+        let counts = [
+            ("views", &statistics.view_count),
+            ("likes", &statistics.like_count),
+            ("comments", &statistics.comment_count),
+        ];
+
+        for (key, count) in counts {
+            if let Some(count) = count.as_ref().and_then(|count| count.parse::<i64>().ok()) {
+                details.insert(key, count);
+            }
+        }
+    }
+
+    if let Some(content_details) = &item.content_details {
+        if let Some(duration) = content_details
+            .duration
+            .as_ref()
+            .and_then(|duration| parse_iso8601_duration(duration))
+        {
+            details.insert("duration", duration);
+        }
+
+        if let Some(definition) = &content_details.definition {
+            details.insert("definition", definition);
+        }
+
+        if let Some(caption) = &content_details.caption {
+            details.insert("caption", caption == "true");
+        }
+    }
+
+    let snippet = &item.snippet;
+
+    if let Some(tags) = &snippet.tags {
+        details.insert("tags", tags);
+    }
+
+    if let Some(category_id) = &snippet.category_id {
+        details.insert("categoryId", category_id);
+    }
+
+    if let Some(language) = snippet
+        .default_language
+        .as_ref()
+        .or(snippet.default_audio_language.as_ref())
+    {
+        details.insert("defaultLanguage", language);
+    }
+
+    if let Some(live_broadcast_content) = &snippet.live_broadcast_content {
+        details.insert("liveBroadcastContent", live_broadcast_content);
+    }
+
+    details
+}
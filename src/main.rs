@@ -3,6 +3,9 @@ use std::str::FromStr;
 use crawler::{
     additional_channel_crawler::AdditionalChannelCrawler,
     channel_discovery_crawler::ChannelDiscoveryCrawler,
+    dormant_channel_crawler::DormantChannelCrawler, influence_crawler::InfluenceCrawler,
+    terminated_channel_crawler::TerminatedChannelCrawler,
+    video_enrichment_crawler::VideoEnrichmentCrawler,
 };
 use figment::{
     providers::{Env, Format, Json},
@@ -10,20 +13,25 @@ use figment::{
 };
 use log::{debug, error, info, LevelFilter};
 use mongodb::{options::ClientOptions, Client};
-use repos::additional_channel_repo::AdditionalChannelRepository;
+use repos::additional_channel_repo::{AdditionalChannelRepository, SubmissionStatus};
 use repos::blacklist_repo::BlacklistRepository;
+use repos::channel_edge_repo::ChannelEdgeRepository;
+use repos::discovery_seed_repo::DiscoverySeedRepository;
 use repos::sailing_term_repo::SailingTermRepository;
+use repos::video_history_repo::VideoHistoryRepository;
+use repos::video_view_repo::VideoViewRepository;
 use simple_logger::SimpleLogger;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 use tokio::task::{self, JoinHandle};
 
 use crate::{
-    commands::crawl_channel_command::CrawlChannelCommand,
+    commands::crawl_channel_command::{CrawlChannelCommand, CrawlPriority},
     repos::{
         settings_repo::SettingsRepository, subscriber_repo::SubscriberRepository,
         view_repo::ViewRepository,
     },
     services::{sailing_terms_service::SailingTermsService, youtube_service::YoutubeService},
+    utils::priority_queue::PriorityQueue,
 };
 use crate::{commands::crawl_videos_command::CrawlVideosCommand, models::config::Config};
 use crate::{
@@ -44,12 +52,16 @@ mod scraper;
 mod services;
 mod utils;
 
+/// Number of scrapes a waiting lower priority lane can be passed over in a row.
+const STARVATION_LIMIT: usize = 10;
+
 #[tokio::main]
 pub async fn main() -> Result<(), anyhow::Error> {
     let config: Config = Figment::new()
         .merge(Json::file("config.json"))
         .merge(Env::raw().only(&["MONGO_CONNECTION_STRING"]))
-        .extract()?;
+        .extract::<Config>()?
+        .validate();
 
     debug!("{:?}", config);
     info!("Environment {}", config.environment);
@@ -75,6 +87,7 @@ pub async fn main() -> Result<(), anyhow::Error> {
         db_client.clone(),
         config.clone(),
         channel_scraper_rx,
+        video_scraper_tx.clone(),
     );
 
     register_video_scraper(
@@ -112,6 +125,19 @@ pub async fn main() -> Result<(), anyhow::Error> {
         video_scraper_tx.clone(),
     );
 
+    register_dormant_channel_crawler(
+        &mut tasks,
+        db_client.clone(),
+        config.clone(),
+        video_scraper_tx.clone(),
+    );
+
+    register_influence_crawler(&mut tasks, db_client.clone(), config.clone());
+
+    register_terminated_channel_crawler(&mut tasks, db_client.clone(), config.clone());
+
+    register_video_enrichment_crawler(&mut tasks, db_client.clone(), config.clone());
+
     await_all(tasks).await;
 
     Ok(())
@@ -129,14 +155,21 @@ fn register_additional_channel_crawler(
     config: Config,
     tx: Sender<CrawlChannelCommand>,
 ) {
-    if config.crawler.additional == false {
+    if !config.crawler.additional {
         return;
     }
 
     let additional_channel_crawling_task = task::spawn(async move {
         let additional_channel_repo =
             AdditionalChannelRepository::new(&mongo_client, &config.environment);
-        let crawler = AdditionalChannelCrawler::new(tx, additional_channel_repo);
+        let apikey_repo = ApiKeyRepository::new(&mongo_client, &config.environment);
+        let youtube_service = YoutubeService::new(apikey_repo);
+        let crawler = AdditionalChannelCrawler::new(
+            tx,
+            additional_channel_repo,
+            youtube_service,
+            config.additional.clone(),
+        );
 
         info!("CRAWLER: Start additional channel crawling");
         crawler
@@ -154,7 +187,7 @@ fn register_channel_discovery_crawler(
     config: Config,
     tx: Sender<CrawlChannelCommand>,
 ) {
-    if config.crawler.discovery == false {
+    if !config.crawler.discovery {
         return;
     }
 
@@ -170,6 +203,9 @@ fn register_channel_discovery_crawler(
             NonSailingChannelRepository::new(&mongo_client, &config.environment);
         let additional_channel_repo =
             AdditionalChannelRepository::new(&mongo_client, &config.environment);
+        let video_repo = VideoRepository::new(&mongo_client, &config.environment);
+        let channel_edge_repo = ChannelEdgeRepository::new(&mongo_client, &config.environment);
+        let discovery_seed_repo = DiscoverySeedRepository::new(&mongo_client, &config.environment);
 
         let youtube_service = YoutubeService::new(apikey_repo);
         let sailing_terms_service = SailingTermsService::new(
@@ -185,6 +221,10 @@ fn register_channel_discovery_crawler(
             youtube_service,
             sailing_terms_service,
             additional_channel_repo,
+            video_repo,
+            channel_edge_repo,
+            discovery_seed_repo,
+            config.discovery.clone(),
         );
 
         info!("CRAWLER: Start channel discovery crawling");
@@ -203,7 +243,7 @@ fn register_channel_update_crawler(
     config: Config,
     tx: Sender<CrawlChannelCommand>,
 ) {
-    if config.crawler.channel == false {
+    if !config.crawler.channel {
         return;
     }
 
@@ -228,13 +268,13 @@ fn register_new_video_crawler(
     config: Config,
     tx: Sender<CrawlVideosCommand>,
 ) {
-    if config.crawler.video == false {
+    if !config.crawler.video {
         return;
     }
 
     let new_video_crawling_task = task::spawn(async move {
         let channel_repo = ChannelRepository::new(&mongo_client, &config.environment);
-        let crawler = NewVideoCrawler::new(tx, channel_repo);
+        let crawler = NewVideoCrawler::new(tx, channel_repo, config.video.clone());
 
         info!("CRAWLER: Start new video crawling");
         let result = crawler.crawl().await;
@@ -247,11 +287,135 @@ fn register_new_video_crawler(
     tasks.push(new_video_crawling_task);
 }
 
+fn register_dormant_channel_crawler(
+    tasks: &mut Vec<JoinHandle<()>>,
+    mongo_client: Client,
+    config: Config,
+    tx: Sender<CrawlVideosCommand>,
+) {
+    if !config.crawler.dormant {
+        return;
+    }
+
+    let dormant_channel_crawling_task = task::spawn(async move {
+        let channel_repo = ChannelRepository::new(&mongo_client, &config.environment);
+        let crawler = DormantChannelCrawler::new(tx, channel_repo);
+
+        info!("CRAWLER: Start dormant channel crawling");
+        let result = crawler.crawl().await;
+
+        if let Err(e) = result {
+            error!("Error in dormant channel crawling: {}", e);
+        }
+    });
+
+    tasks.push(dormant_channel_crawling_task);
+}
+
+fn register_influence_crawler(
+    tasks: &mut Vec<JoinHandle<()>>,
+    mongo_client: Client,
+    config: Config,
+) {
+    if !config.crawler.influence {
+        return;
+    }
+
+    let influence_crawling_task = task::spawn(async move {
+        let channel_repo = ChannelRepository::new(&mongo_client, &config.environment);
+        let channel_edge_repo = ChannelEdgeRepository::new(&mongo_client, &config.environment);
+        let crawler = InfluenceCrawler::new(channel_repo, channel_edge_repo);
+
+        info!("CRAWLER: Start influence crawling");
+        let result = crawler.crawl().await;
+
+        if let Err(e) = result {
+            error!("Error in influence crawling: {}", e);
+        }
+    });
+
+    tasks.push(influence_crawling_task);
+}
+
+fn register_terminated_channel_crawler(
+    tasks: &mut Vec<JoinHandle<()>>,
+    mongo_client: Client,
+    config: Config,
+) {
+    if !config.crawler.terminated {
+        return;
+    }
+
+    let terminated_channel_crawling_task = task::spawn(async move {
+        let channel_repo = ChannelRepository::new(&mongo_client, &config.environment);
+        let view_repo = ViewRepository::new(&mongo_client, &config.environment);
+        let subscriber_repo = SubscriberRepository::new(&mongo_client, &config.environment);
+        let video_repo = VideoRepository::new(&mongo_client, &config.environment);
+        let video_view_repo = VideoViewRepository::new(&mongo_client, &config.environment);
+        let video_history_repo = VideoHistoryRepository::new(&mongo_client, &config.environment);
+        let channel_edge_repo = ChannelEdgeRepository::new(&mongo_client, &config.environment);
+        let discovery_seed_repo = DiscoverySeedRepository::new(&mongo_client, &config.environment);
+        let crawler = TerminatedChannelCrawler::new(
+            channel_repo,
+            view_repo,
+            subscriber_repo,
+            video_repo,
+            video_view_repo,
+            video_history_repo,
+            channel_edge_repo,
+            discovery_seed_repo,
+            config.channel.purge_after_days,
+        );
+
+        info!("CRAWLER: Start terminated channel crawling");
+        let result = crawler.crawl().await;
+
+        if let Err(e) = result {
+            error!("Error in terminated channel crawling: {}", e);
+        }
+    });
+
+    tasks.push(terminated_channel_crawling_task);
+}
+
+fn register_video_enrichment_crawler(
+    tasks: &mut Vec<JoinHandle<()>>,
+    mongo_client: Client,
+    config: Config,
+) {
+    if !config.crawler.enrichment {
+        return;
+    }
+
+    let video_enrichment_crawling_task = task::spawn(async move {
+        let video_repo = VideoRepository::new(&mongo_client, &config.environment);
+        let channel_repo = ChannelRepository::new(&mongo_client, &config.environment);
+        let apikey_repo = ApiKeyRepository::new(&mongo_client, &config.environment);
+        let youtube_service = YoutubeService::new(apikey_repo);
+        let crawler = VideoEnrichmentCrawler::new(
+            video_repo,
+            channel_repo,
+            youtube_service,
+            config.video.clone(),
+        );
+
+        info!("CRAWLER: Start video enrichment crawling");
+        let result = crawler.crawl().await;
+
+        if let Err(e) = result {
+            error!("Error in video enrichment crawling: {}", e);
+        }
+    });
+
+    tasks.push(video_enrichment_crawling_task);
+}
+
 fn register_channel_scraper(
     tasks: &mut Vec<JoinHandle<()>>,
     mongo_client: Client,
     config: Config,
     mut rx: Receiver<CrawlChannelCommand>,
+    video_tx: Sender<CrawlVideosCommand>,
 ) {
     let channel_scraper_task = task::spawn(async move {
         info!("SCRAPER: Start channel scrape listener");
@@ -262,6 +426,8 @@ fn register_channel_scraper(
         let view_repo = ViewRepository::new(&mongo_client, &config.environment);
         let subscriber_repo = SubscriberRepository::new(&mongo_client, &config.environment);
         let video_repo = VideoRepository::new(&mongo_client, &config.environment);
+        let video_view_repo = VideoViewRepository::new(&mongo_client, &config.environment);
+        let video_history_repo = VideoHistoryRepository::new(&mongo_client, &config.environment);
         let apikey_repo = ApiKeyRepository::new(&mongo_client, &config.environment);
 
         let sailing_terms = get_sailing_terms(&mongo_client, &config.environment).await;
@@ -279,18 +445,53 @@ fn register_channel_scraper(
             view_repo,
             subscriber_repo,
             video_repo,
+            video_view_repo,
+            video_history_repo,
             apikey_repo,
             sailing_terms_service,
+            video_tx,
+            config.channel.clone(),
         );
 
-        while let Some(cmd) = rx.recv().await {
+        let additional_channel_repo =
+            AdditionalChannelRepository::new(&mongo_client, &config.environment);
+
+        let mut queue = PriorityQueue::new(CrawlPriority::COUNT, STARVATION_LIMIT);
+
+        loop {
+            while let Ok(cmd) = rx.try_recv() {
+                queue.push(cmd.priority.lane(), cmd);
+            }
+
+            let cmd = match queue.pop() {
+                Some(cmd) => cmd,
+                None => match rx.recv().await {
+                    Some(cmd) => cmd,
+                    None => break,
+                },
+            };
+
+            debug!("{} channels waiting for scraping", queue.len());
+
             let result = scraper
-                .scrape(cmd.channel_id, cmd.ignore_sailing_terms)
+                .scrape(cmd.channel_id, cmd.ignore_sailing_terms, cmd.discovery)
                 .await;
 
-            if let Err(e) = result {
+            if let Err(e) = &result {
                 error!("Error in channel scraping: {}", e);
             }
+
+            if let Some(submission_id) = cmd.submission_id {
+                let (status, reason) = SubmissionStatus::from_scrape_result(result);
+
+                let result = additional_channel_repo
+                    .set_finished(&submission_id, status, reason)
+                    .await;
+
+                if let Err(e) = result {
+                    error!("Error in storing submission status: {}", e);
+                }
+            }
         }
     });
 
@@ -308,10 +509,21 @@ fn register_video_scraper(
 
         let video_repo = VideoRepository::new(&mongo_client, &config.environment);
         let channel_repo = ChannelRepository::new(&mongo_client, &config.environment);
-        let scraper = VideoScraper::new(video_repo, channel_repo);
+        let apikey_repo = ApiKeyRepository::new(&mongo_client, &config.environment);
+        let video_view_repo = VideoViewRepository::new(&mongo_client, &config.environment);
+        let video_history_repo = VideoHistoryRepository::new(&mongo_client, &config.environment);
+        let youtube_service = YoutubeService::new(apikey_repo);
+        let scraper = VideoScraper::new(
+            video_repo,
+            channel_repo,
+            video_view_repo,
+            video_history_repo,
+            youtube_service,
+            config.video.clone(),
+        );
 
         while let Some(cmd) = rx.recv().await {
-            let result = scraper.scrape(cmd.channel_id).await;
+            let result = scraper.scrape(cmd.channel_id, cmd.backfill).await;
 
             if let Err(e) = result {
                 error!("Error in video scraper: {}", e);
@@ -323,14 +535,14 @@ fn register_video_scraper(
 }
 
 async fn get_sailing_terms(mongo_client: &Client, environment: &str) -> Vec<String> {
-    let sailing_term_repo = SailingTermRepository::new(&mongo_client, environment);
+    let sailing_term_repo = SailingTermRepository::new(mongo_client, environment);
     let sailing_terms = sailing_term_repo.get_all().await.unwrap();
 
     sailing_terms
 }
 
 async fn get_blacklisted_channels(mongo_client: &Client, environment: &str) -> Vec<String> {
-    let blacklist_repo = BlacklistRepository::new(&mongo_client, environment);
+    let blacklist_repo = BlacklistRepository::new(mongo_client, environment);
     let blacklisted_channels = blacklist_repo.get_all().await.unwrap();
 
     blacklisted_channels
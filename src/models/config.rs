@@ -6,6 +6,148 @@ pub struct CrawlerConfig {
     pub discovery: bool,
     pub video: bool,
     pub channel: bool,
+    #[serde(default)]
+    pub influence: bool,
+    #[serde(default)]
+    pub dormant: bool,
+    #[serde(default)]
+    pub enrichment: bool,
+    #[serde(default)]
+    pub terminated: bool,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct DiscoveryConfig {
+    #[serde(default)]
+    pub search_quota_budget: i64,
+    #[serde(default = "default_search_languages")]
+    pub search_languages: Vec<String>,
+    #[serde(default = "default_max_depth")]
+    pub max_depth: u32,
+    #[serde(default = "default_depth_decay")]
+    pub depth_decay: f64,
+    #[serde(default = "default_min_score")]
+    pub min_score: f64,
+    #[serde(default)]
+    pub quota_budget: i64,
+    #[serde(default = "default_mention_quota_budget")]
+    pub mention_quota_budget: i64,
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        DiscoveryConfig {
+            search_quota_budget: 0,
+            search_languages: default_search_languages(),
+            max_depth: default_max_depth(),
+            depth_decay: default_depth_decay(),
+            min_score: default_min_score(),
+            quota_budget: 0,
+            mention_quota_budget: default_mention_quota_budget(),
+        }
+    }
+}
+
+fn default_mention_quota_budget() -> i64 {
+    500
+}
+
+fn default_max_depth() -> u32 {
+    1
+}
+
+fn default_depth_decay() -> f64 {
+    0.5
+}
+
+fn default_min_score() -> f64 {
+    0.2
+}
+
+fn default_search_languages() -> Vec<String> {
+    vec!["en".to_string()]
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct AdditionalConfig {
+    #[serde(default = "default_retention_days")]
+    pub retention_days: i64,
+}
+
+impl Default for AdditionalConfig {
+    fn default() -> Self {
+        AdditionalConfig {
+            retention_days: default_retention_days(),
+        }
+    }
+}
+
+fn default_retention_days() -> i64 {
+    30
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct VideoConfig {
+    #[serde(default = "default_min_poll_interval_hours")]
+    pub min_poll_interval_hours: i64,
+    #[serde(default = "default_max_poll_interval_hours")]
+    pub max_poll_interval_hours: i64,
+    #[serde(default = "default_enrichment_quota_budget")]
+    pub enrichment_quota_budget: i64,
+    #[serde(default = "default_backfill_max_pages")]
+    pub backfill_max_pages: i64,
+}
+
+impl Default for VideoConfig {
+    fn default() -> Self {
+        VideoConfig {
+            min_poll_interval_hours: default_min_poll_interval_hours(),
+            max_poll_interval_hours: default_max_poll_interval_hours(),
+            enrichment_quota_budget: default_enrichment_quota_budget(),
+            backfill_max_pages: default_backfill_max_pages(),
+        }
+    }
+}
+
+fn default_enrichment_quota_budget() -> i64 {
+    1000
+}
+
+fn default_backfill_max_pages() -> i64 {
+    100
+}
+
+fn default_min_poll_interval_hours() -> i64 {
+    1
+}
+
+fn default_max_poll_interval_hours() -> i64 {
+    7 * 24
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ChannelConfig {
+    #[serde(default = "default_not_found_threshold")]
+    pub not_found_threshold: i32,
+    #[serde(default = "default_purge_after_days")]
+    pub purge_after_days: i64,
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        ChannelConfig {
+            not_found_threshold: default_not_found_threshold(),
+            purge_after_days: default_purge_after_days(),
+        }
+    }
+}
+
+fn default_purge_after_days() -> i64 {
+    30
+}
+
+fn default_not_found_threshold() -> i32 {
+    3
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -14,4 +156,25 @@ pub struct Config {
     pub environment: String,
     pub log_level: String,
     pub crawler: CrawlerConfig,
+    #[serde(default)]
+    pub discovery: DiscoveryConfig,
+    #[serde(default)]
+    pub additional: AdditionalConfig,
+    #[serde(default)]
+    pub video: VideoConfig,
+    #[serde(default)]
+    pub channel: ChannelConfig,
+}
+
+impl Config {
+    /// Clamps values that would otherwise make crawlers spin without waiting.
+    pub fn validate(mut self) -> Self {
+        self.video.min_poll_interval_hours = self.video.min_poll_interval_hours.max(1);
+        self.video.max_poll_interval_hours = self
+            .video
+            .max_poll_interval_hours
+            .max(self.video.min_poll_interval_hours);
+
+        self
+    }
 }
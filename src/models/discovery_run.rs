@@ -0,0 +1,44 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoveryRun {
+    pub run_id: String,
+    pub seeds: Vec<String>,
+    pub cursor: usize,
+    pub used_quota: i64,
+    pub started_at: i64,
+    #[serde(default)]
+    pub usable_seeds: usize,
+    #[serde(default)]
+    pub private_seeds: usize,
+    #[serde(default)]
+    pub failed_seeds: usize,
+    #[serde(default)]
+    pub skipped_seeds: usize,
+    /// Expansion state of the seed at `cursor` when the run paused within it.
+    #[serde(default)]
+    pub seed_progress: Option<SeedProgress>,
+}
+
+/// The breadth first expansion of a seed channel: the channels left on the current
+/// hop, the channels accepted for the next hop and all channels seen so far.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeedProgress {
+    pub depth: u32,
+    pub frontier: Vec<FrontierChannel>,
+    pub next_frontier: Vec<FrontierChannel>,
+    pub visited: HashSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontierChannel {
+    pub channel_id: String,
+    pub score: f64,
+    /// Featured channels known from loaded channel details, which saves a request.
+    pub featured_channel_ids: Option<Vec<String>>,
+}
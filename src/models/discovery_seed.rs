@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiscoverySeed {
+    #[serde(rename = "_id")]
+    pub channel_id: String,
+    pub subscriptions_etag: Option<String>,
+    pub subscriptions_status: Option<String>,
+    #[serde(default)]
+    pub failures: i32,
+    pub next_attempt_at: Option<i64>,
+}
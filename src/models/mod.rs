@@ -1,5 +1,13 @@
 pub mod apikey;
 pub mod config;
+pub mod discovery_run;
+pub mod discovery_seed;
 pub mod youtube_channel_details;
+pub mod youtube_channel_sections;
 pub mod youtube_channel_subscriptions;
+pub mod youtube_error_response;
+pub mod youtube_id_list;
+pub mod youtube_playlist_items;
+pub mod youtube_search_results;
+pub mod youtube_video_details;
 pub mod youtube_video_feed_response;
@@ -104,6 +104,7 @@ pub struct Channel {
     pub moderate_comments: Option<bool>,
     pub unsubscribed_trailer: Option<String>,
     pub country: Option<String>,
+    pub featured_channels_urls: Option<Vec<String>>,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
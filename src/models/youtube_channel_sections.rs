@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubeChannelSections {
+    pub kind: String,
+    pub etag: String,
+    #[serde(default)]
+    pub items: Vec<Item>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Item {
+    pub kind: String,
+    pub etag: String,
+    pub id: String,
+    pub snippet: ChannelSectionSnippet,
+    pub content_details: Option<ContentDetails>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelSectionSnippet {
+    #[serde(rename = "type")]
+    pub section_type: String,
+    pub channel_id: String,
+    pub title: Option<String>,
+    pub position: i64,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentDetails {
+    pub playlists: Option<Vec<String>>,
+    pub channels: Option<Vec<String>>,
+}
@@ -0,0 +1,39 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubeErrorResponse {
+    pub error: YoutubeApiError,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubeApiError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default)]
+    pub errors: Vec<ErrorDetail>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorDetail {
+    pub reason: String,
+    pub message: Option<String>,
+}
+
+impl YoutubeApiError {
+    pub fn has_reason(&self, reason: &str) -> bool {
+        self.errors.iter().any(|detail| detail.reason == reason)
+    }
+}
+
+impl fmt::Display for YoutubeApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Youtube API Error {}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for YoutubeApiError {}
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubeIdList {
+    pub kind: String,
+    pub etag: String,
+    pub items: Option<Vec<IdItem>>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdItem {
+    pub kind: String,
+    pub etag: String,
+    pub id: String,
+}
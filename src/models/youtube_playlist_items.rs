@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubePlaylistItems {
+    pub kind: String,
+    pub etag: String,
+    #[serde(default)]
+    pub items: Vec<PlaylistItem>,
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistItem {
+    pub kind: String,
+    pub etag: String,
+    pub id: String,
+    pub snippet: PlaylistItemSnippet,
+    pub content_details: PlaylistItemContentDetails,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistItemSnippet {
+    pub published_at: String,
+    pub channel_id: String,
+    pub title: String,
+    pub description: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistItemContentDetails {
+    pub video_id: String,
+    pub video_published_at: Option<String>,
+}
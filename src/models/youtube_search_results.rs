@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubeSearchResults {
+    pub kind: String,
+    pub etag: String,
+    #[serde(default)]
+    pub items: Vec<Item>,
+    pub next_page_token: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Item {
+    pub kind: String,
+    pub etag: String,
+    pub id: SearchResultId,
+    pub snippet: SearchResultSnippet,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResultId {
+    pub kind: String,
+    pub channel_id: Option<String>,
+    pub video_id: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchResultSnippet {
+    pub published_at: String,
+    pub channel_id: String,
+    pub title: String,
+    pub description: String,
+    pub channel_title: String,
+}
@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubeVideoDetails {
+    pub kind: String,
+    pub etag: String,
+    #[serde(default)]
+    pub items: Vec<YoutubeVideoItem>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YoutubeVideoItem {
+    pub kind: String,
+    pub etag: String,
+    pub id: String,
+    pub snippet: VideoSnippet,
+    pub statistics: Option<VideoStatistics>,
+    pub content_details: Option<VideoContentDetails>,
+    pub live_streaming_details: Option<LiveStreamingDetails>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoSnippet {
+    pub published_at: String,
+    pub channel_id: String,
+    pub title: String,
+    pub description: String,
+    pub channel_title: String,
+    pub tags: Option<Vec<String>>,
+    pub category_id: Option<String>,
+    pub default_language: Option<String>,
+    pub default_audio_language: Option<String>,
+    pub live_broadcast_content: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoStatistics {
+    pub view_count: Option<String>,
+    pub like_count: Option<String>,
+    pub comment_count: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VideoContentDetails {
+    pub duration: Option<String>,
+    pub definition: Option<String>,
+    pub caption: Option<String>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveStreamingDetails {
+    pub actual_start_time: Option<String>,
+    pub actual_end_time: Option<String>,
+    pub scheduled_start_time: Option<String>,
+}
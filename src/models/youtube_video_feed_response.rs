@@ -1,44 +1,59 @@
-use serde::Deserialize;
-
-#[derive(Debug, Clone, PartialEq, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct YoutubeVideoFeedResponse {
-    #[serde(rename = "entry", default)]
     pub entries: Vec<Entry>,
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct Entry {
-    #[serde(rename = "ytvideoId")]
     pub video_id: String,
     pub title: String,
     pub published: String,
     pub updated: String,
-    #[serde(rename = "mediagroup")]
+    pub link: Option<Link>,
+    pub author: Option<Author>,
     pub group: MediaGroup,
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Link {
+    pub rel: Option<String>,
+    pub href: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Author {
+    pub name: String,
+    pub uri: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct MediaGroup {
-    #[serde(rename = "mediatitle")]
-    pub title: String,
-    #[serde(rename = "mediadescription")]
+    pub title: Option<String>,
     pub description: String,
-    #[serde(rename = "mediacommunity")]
+    pub thumbnail: Option<MediaThumbnail>,
     pub community: MediaCommunity,
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MediaThumbnail {
+    pub url: String,
+    pub width: Option<i64>,
+    pub height: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct MediaCommunity {
-    #[serde(rename = "mediastatistics")]
-    pub statistics: MediaStatistics,
+    pub star_rating: Option<MediaStarRating>,
+    pub statistics: Option<MediaStatistics>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MediaStarRating {
+    pub count: i64,
+    pub average: f64,
 }
 
-#[derive(Debug, Clone, PartialEq, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct MediaStatistics {
     pub views: i64,
 }
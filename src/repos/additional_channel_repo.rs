@@ -1,42 +1,187 @@
 use anyhow::Error;
+use chrono::Utc;
 use futures::stream::TryStreamExt;
-use mongodb::bson::{doc, Document};
+use mongodb::bson::{doc, DateTime, Document};
+use mongodb::change_stream::{event::ChangeStreamEvent, ChangeStream};
 use mongodb::{Client, Collection};
 
+use crate::scraper::channel_scraper::ScrapeOutcome;
 use crate::utils::db::get_db_name;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmissionStatus {
+    Pending,
+    Processing,
+    Accepted,
+    Rejected,
+    Failed,
+}
+
+impl SubmissionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubmissionStatus::Pending => "pending",
+            SubmissionStatus::Processing => "processing",
+            SubmissionStatus::Accepted => "accepted",
+            SubmissionStatus::Rejected => "rejected",
+            SubmissionStatus::Failed => "failed",
+        }
+    }
+
+    /// Maps the result of scraping a submitted channel to the final submission
+    /// status and the reason shown to the submitter.
+    pub fn from_scrape_result(
+        result: Result<ScrapeOutcome, Error>,
+    ) -> (SubmissionStatus, Option<String>) {
+        match result {
+            Ok(ScrapeOutcome::Accepted) => (SubmissionStatus::Accepted, None),
+            Ok(ScrapeOutcome::Rejected(reason)) => (SubmissionStatus::Rejected, Some(reason)),
+            Ok(ScrapeOutcome::Failed(reason)) => (SubmissionStatus::Failed, Some(reason)),
+            Err(e) => (SubmissionStatus::Failed, Some(e.to_string())),
+        }
+    }
+
+    fn finished() -> Vec<&'static str> {
+        vec![
+            SubmissionStatus::Accepted.as_str(),
+            SubmissionStatus::Rejected.as_str(),
+            SubmissionStatus::Failed.as_str(),
+        ]
+    }
+}
+
 pub struct AdditionalChannelRepository {
     collection: Collection<Document>,
 }
 
 impl AdditionalChannelRepository {
     pub fn new(client: &Client, environment: &str) -> AdditionalChannelRepository {
-        let db = client.database(&get_db_name(&environment));
+        let db = client.database(&get_db_name(environment));
         let feeds = db.collection::<Document>("additional");
 
         AdditionalChannelRepository { collection: feeds }
     }
 
+    /// Only submissions that are not finished yet count as existing.
+    /// Whether an unfinished submission exists for a channel, either submitted by its
+    /// id or resolved to it from a handle or url.
     pub async fn exists(&self, channel_id: &str) -> Result<bool, Error> {
         let result = self
             .collection
-            .count_documents(doc! { "_id": channel_id }, None)
+            .count_documents(
+                doc! {
+                    "$or": [{"_id": channel_id}, {"channelId": channel_id}],
+                    "status": {"$nin": SubmissionStatus::finished()}
+                },
+                None,
+            )
             .await?;
 
         Ok(result > 0)
     }
 
-    pub async fn get_all(&self) -> Result<Vec<Document>, Error> {
-        let cursor = self.collection.find(None, None).await?;
+    /// Returns new submissions and submissions that got stuck in processing, e.g.
+    /// because the process was restarted before the channel was scraped.
+    pub async fn get_pending(
+        &self,
+        stuck_processing_before: DateTime,
+    ) -> Result<Vec<Document>, Error> {
+        let filter = doc! {
+            "$or": [
+                {"status": {"$exists": false}},
+                {"status": SubmissionStatus::Pending.as_str()},
+                {
+                    "status": SubmissionStatus::Processing.as_str(),
+                    "statusChangedAt": {"$lt": stuck_processing_before}
+                }
+            ]
+        };
+
+        let cursor = self.collection.find(filter, None).await?;
         let additional_channels: Vec<Document> = cursor.try_collect().await?;
 
         Ok(additional_channels)
     }
 
-    pub async fn delete_one(&self, id: &str) -> Result<(), Error> {
-        let filter = doc! {"_id": id};
-        self.collection.delete_one(filter, None).await?;
+    /// Opens a change stream on newly submitted channels. Fails if the deployment
+    /// does not support change streams, e.g. on a standalone server.
+    pub async fn watch_submissions(
+        &self,
+    ) -> Result<ChangeStream<ChangeStreamEvent<Document>>, Error> {
+        let pipeline = vec![doc! {
+            "$match": {
+                "operationType": {"$in": ["insert", "replace"]}
+            }
+        }];
+
+        let change_stream = self.collection.watch(pipeline, None).await?;
+
+        Ok(change_stream)
+    }
+
+    pub async fn set_processing(&self, id: &str, channel_id: &str) -> Result<(), Error> {
+        self.collection
+            .update_one(
+                doc! {"_id": id},
+                doc! {
+                    "$set": {
+                        "status": SubmissionStatus::Processing.as_str(),
+                        "channelId": channel_id,
+                        "statusChangedAt": DateTime::now(),
+                    },
+                    "$unset": {
+                        "reason": "",
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_finished(
+        &self,
+        id: &str,
+        status: SubmissionStatus,
+        reason: Option<String>,
+    ) -> Result<(), Error> {
+        let now = DateTime::now();
+
+        self.collection
+            .update_one(
+                doc! {"_id": id},
+                doc! {
+                    "$set": {
+                        "status": status.as_str(),
+                        "reason": reason,
+                        "statusChangedAt": now,
+                        "finishedAt": now,
+                    }
+                },
+                None,
+            )
+            .await?;
 
         Ok(())
     }
+
+    pub async fn delete_finished_before(&self, retention_days: i64) -> Result<u64, Error> {
+        let finished_before = Utc::now() - chrono::Duration::days(retention_days);
+
+        let result = self
+            .collection
+            .delete_many(
+                doc! {
+                    "status": {"$in": SubmissionStatus::finished()},
+                    "finishedAt": {
+                        "$lt": DateTime::from_millis(finished_before.timestamp_millis())
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        Ok(result.deleted_count)
+    }
 }
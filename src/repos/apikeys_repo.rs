@@ -14,7 +14,7 @@ pub struct ApiKeyRepository {
 
 impl ApiKeyRepository {
     pub fn new(client: &Client, environment: &str) -> ApiKeyRepository {
-        let db = client.database(&get_db_name(&environment));
+        let db = client.database(&get_db_name(environment));
         let channels = db.collection::<ApiKey>("apikeys");
 
         ApiKeyRepository {
@@ -36,7 +36,7 @@ impl ApiKeyRepository {
         Ok(doc)
     }
 
-    pub async fn update_usage(&self, api_key: &ApiKey) -> Result<(), Error> {
+    pub async fn update_usage(&self, api_key: &ApiKey, cost: i32) -> Result<(), Error> {
         let pacific_now: DateTime<Tz> = Utc::now().with_timezone(&Pacific);
         let pacific_date = pacific_now
             .format("%Y%m%d")
@@ -46,14 +46,14 @@ impl ApiKeyRepository {
 
         let mut update = doc! {
             "$inc": {
-                "used_quota": 1
+                "used_quota": cost
             }
         };
 
         if pacific_date > api_key.pdt_day {
             update = doc! {
                 "$set": {
-                    "used_quota": 1,
+                    "used_quota": cost,
                     "pdt_day": pacific_date,
                 }
             };
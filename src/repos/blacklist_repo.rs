@@ -11,7 +11,7 @@ pub struct BlacklistRepository {
 
 impl BlacklistRepository {
     pub fn new(client: &Client, environment: &str) -> BlacklistRepository {
-        let db = client.database(&get_db_name(&environment));
+        let db = client.database(&get_db_name(environment));
         let feeds = db.collection::<Document>("blacklist");
 
         BlacklistRepository { collection: feeds }
@@ -0,0 +1,129 @@
+use anyhow::Error;
+use futures::stream::TryStreamExt;
+use mongodb::bson::{doc, DateTime, Document};
+use mongodb::options::{FindOptions, UpdateOptions};
+use mongodb::{Client, Collection};
+
+use crate::utils::db::get_db_name;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeSource {
+    Subscription,
+    FeaturedChannel,
+    Mention,
+}
+
+impl EdgeSource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeSource::Subscription => "subscription",
+            EdgeSource::FeaturedChannel => "featured",
+            EdgeSource::Mention => "mention",
+        }
+    }
+}
+
+pub struct ChannelEdgeRepository {
+    collection: Collection<Document>,
+}
+
+impl ChannelEdgeRepository {
+    pub fn new(client: &Client, environment: &str) -> ChannelEdgeRepository {
+        let db = client.database(&get_db_name(environment));
+        let edges = db.collection::<Document>("channeledges");
+
+        ChannelEdgeRepository { collection: edges }
+    }
+
+    pub async fn upsert(&self, from: &str, to: &str, source: EdgeSource) -> Result<(), Error> {
+        let update_options = UpdateOptions::builder().upsert(true).build();
+        let now = DateTime::now();
+
+        self.collection
+            .update_one(
+                doc! {"_id": {"from": from, "to": to, "source": source.as_str()}},
+                doc! {
+                    "$set": {
+                        "from": from,
+                        "to": to,
+                        "source": source.as_str(),
+                        "lastSeenAt": now,
+                    },
+                    "$setOnInsert": {
+                        "firstSeenAt": now,
+                    }
+                },
+                update_options,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Deletes all edges from and to a channel.
+    pub async fn delete_by_channel(&self, channel_id: &str) -> Result<(), Error> {
+        self.collection
+            .delete_many(
+                doc! {"$or": [{"from": channel_id}, {"to": channel_id}]},
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn touch_all_from(&self, from: &str, source: EdgeSource) -> Result<(), Error> {
+        self.collection
+            .update_many(
+                doc! {"from": from, "source": source.as_str()},
+                doc! {"$set": {"lastSeenAt": DateTime::now()}},
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Returns the channels a channel points to with edges of a source.
+    pub async fn get_targets_from(
+        &self,
+        from: &str,
+        source: EdgeSource,
+    ) -> Result<Vec<String>, Error> {
+        let find_options = FindOptions::builder().projection(doc! {"to": 1}).build();
+
+        let cursor = self
+            .collection
+            .find(doc! {"from": from, "source": source.as_str()}, find_options)
+            .await?;
+        let edges: Vec<Document> = cursor.try_collect().await?;
+
+        let targets = edges
+            .iter()
+            .filter_map(|doc| doc.get_str("to").ok().map(|to| to.to_string()))
+            .collect();
+
+        Ok(targets)
+    }
+
+    pub async fn get_all(&self) -> Result<Vec<(String, String)>, Error> {
+        let find_options = FindOptions::builder()
+            .projection(doc! {"from": 1, "to": 1})
+            .build();
+
+        let cursor = self.collection.find(None, find_options).await?;
+        let edges: Vec<Document> = cursor.try_collect().await?;
+
+        let edges = edges
+            .iter()
+            .filter_map(|doc| {
+                let from = doc.get_str("from").ok()?;
+                let to = doc.get_str("to").ok()?;
+
+                Some((from.to_string(), to.to_string()))
+            })
+            .collect();
+
+        Ok(edges)
+    }
+}
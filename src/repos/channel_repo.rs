@@ -2,18 +2,67 @@ use anyhow::Error;
 use chrono::Utc;
 use futures::stream::TryStreamExt;
 use mongodb::bson::{doc, Document};
-use mongodb::options::{FindOneOptions, FindOptions};
+use mongodb::options::{FindOneAndUpdateOptions, FindOneOptions, FindOptions, ReturnDocument};
 use mongodb::{Client, Collection};
 
 use crate::utils::db::get_db_name;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelStatus {
+    Active,
+    Dormant,
+    Revived,
+    Terminated,
+}
+
+impl ChannelStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChannelStatus::Active => "active",
+            ChannelStatus::Dormant => "dormant",
+            ChannelStatus::Revived => "revived",
+            ChannelStatus::Terminated => "terminated",
+        }
+    }
+
+    fn inactive() -> Vec<&'static str> {
+        vec![
+            ChannelStatus::Dormant.as_str(),
+            ChannelStatus::Terminated.as_str(),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrapeErrorType {
+    NotFound,
+    QuotaExceeded,
+    Api,
+    Network,
+    Other,
+}
+
+impl ScrapeErrorType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScrapeErrorType::NotFound => "notFound",
+            ScrapeErrorType::QuotaExceeded => "quotaExceeded",
+            ScrapeErrorType::Api => "api",
+            ScrapeErrorType::Network => "network",
+            ScrapeErrorType::Other => "other",
+        }
+    }
+}
+
+const MAX_SCRAPE_ERRORS: i32 = 10;
+
 pub struct ChannelRepository {
     collection: Collection<Document>,
 }
 
 impl ChannelRepository {
     pub fn new(client: &Client, environment: &str) -> ChannelRepository {
-        let db = client.database(&get_db_name(&environment));
+        let db = client.database(&get_db_name(environment));
         let channels = db.collection::<Document>("channels");
 
         ChannelRepository {
@@ -43,6 +92,143 @@ impl ChannelRepository {
         Ok(channel_ids)
     }
 
+    /// Returns channels whose video feed is due for polling, including channels
+    /// that have never been polled.
+    pub async fn get_ids_video_poll_due(&self) -> Result<Vec<String>, Error> {
+        let find_options = FindOptions::builder().projection(doc! { "_id": 1 }).build();
+
+        let query = doc! {
+            "status": {"$nin": ChannelStatus::inactive()},
+            "$or": [
+                {"nextVideoPollAt": {"$exists": false}},
+                {"nextVideoPollAt": {"$lte": mongodb::bson::DateTime::now()}}
+            ]
+        };
+
+        let cursor = self.collection.find(query, find_options).await?;
+        let channels: Vec<Document> = cursor.try_collect().await?;
+
+        let channel_ids = channels
+            .iter()
+            .map(|doc| doc.get_str("_id").unwrap().to_string())
+            .collect();
+
+        Ok(channel_ids)
+    }
+
+    pub async fn get_dormant_ids(&self) -> Result<Vec<String>, Error> {
+        let find_options = FindOptions::builder().projection(doc! { "_id": 1 }).build();
+
+        let query = doc! {"status": ChannelStatus::Dormant.as_str()};
+
+        let cursor = self.collection.find(query, find_options).await?;
+        let channels: Vec<Document> = cursor.try_collect().await?;
+
+        let channel_ids = channels
+            .iter()
+            .map(|doc| doc.get_str("_id").unwrap().to_string())
+            .collect();
+
+        Ok(channel_ids)
+    }
+
+    /// Marks channels without uploads since `last_upload_before` as dormant and
+    /// channels without a status that still upload as active.
+    pub async fn update_dormant_status(
+        &self,
+        last_upload_before: chrono::DateTime<Utc>,
+    ) -> Result<u64, Error> {
+        let result = self
+            .collection
+            .update_many(
+                doc! {
+                    "status": {"$nin": ChannelStatus::inactive()},
+                    "lastUploadAt": {"$lt": last_upload_before.timestamp()}
+                },
+                doc! {
+                    "$set": {
+                        "status": ChannelStatus::Dormant.as_str(),
+                        "dormantSince": mongodb::bson::DateTime::now()
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        self.collection
+            .update_many(
+                doc! {
+                    "status": {"$exists": false},
+                    "lastUploadAt": {"$gte": last_upload_before.timestamp()}
+                },
+                doc! {"$set": {"status": ChannelStatus::Active.as_str()}},
+                None,
+            )
+            .await?;
+
+        Ok(result.modified_count)
+    }
+
+    /// Marks a dormant channel as revived. Returns whether the channel was dormant.
+    pub async fn revive_if_dormant(&self, id: &str) -> Result<bool, Error> {
+        let result = self
+            .collection
+            .update_one(
+                doc! {"_id": id, "status": ChannelStatus::Dormant.as_str()},
+                doc! {
+                    "$set": {
+                        "status": ChannelStatus::Revived.as_str(),
+                        "revivedAt": mongodb::bson::DateTime::now()
+                    },
+                    "$unset": {
+                        "dormantSince": ""
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        Ok(result.modified_count > 0)
+    }
+
+    /// Marks channels revived since the last sweep as active again.
+    pub async fn activate_revived(&self) -> Result<u64, Error> {
+        let result = self
+            .collection
+            .update_many(
+                doc! {"status": ChannelStatus::Revived.as_str()},
+                doc! {"$set": {"status": ChannelStatus::Active.as_str()}},
+                None,
+            )
+            .await?;
+
+        Ok(result.modified_count)
+    }
+
+    pub async fn get_ids_terminated_before(
+        &self,
+        terminated_before: chrono::DateTime<Utc>,
+    ) -> Result<Vec<String>, Error> {
+        let find_options = FindOptions::builder().projection(doc! { "_id": 1 }).build();
+
+        let query = doc! {
+            "status": ChannelStatus::Terminated.as_str(),
+            "terminatedAt": {
+                "$lt": mongodb::bson::DateTime::from_millis(terminated_before.timestamp_millis())
+            }
+        };
+
+        let cursor = self.collection.find(query, find_options).await?;
+        let channels: Vec<Document> = cursor.try_collect().await?;
+
+        let channel_ids = channels
+            .iter()
+            .map(|doc| doc.get_str("_id").unwrap().to_string())
+            .collect();
+
+        Ok(channel_ids)
+    }
+
     pub async fn get_ids_upload_last_month(
         &self,
         min_subscribers_count: i64,
@@ -52,6 +238,7 @@ impl ChannelRepository {
         let one_month_ago = Utc::now() - chrono::Duration::weeks(4);
 
         let query = doc! {
+            "status": {"$ne": ChannelStatus::Terminated.as_str()},
             "lastUploadAt": {
                 "$gte": one_month_ago.timestamp()
             },
@@ -83,6 +270,7 @@ impl ChannelRepository {
             .build();
 
         let query = doc! {
+            "status": {"$ne": ChannelStatus::Terminated.as_str()},
             "$and": [{
                 "lastCrawl": {
                     "$lt": mongodb::bson::DateTime::from_millis(
@@ -93,6 +281,11 @@ impl ChannelRepository {
                 "lastUploadAt": {
                     "$gte": last_upload_after.timestamp()
                 }
+            }, {
+                "$or": [
+                    {"nextScrapeAt": {"$exists": false}},
+                    {"nextScrapeAt": {"$lte": mongodb::bson::DateTime::now()}}
+                ]
             }]
         };
 
@@ -129,15 +322,30 @@ impl ChannelRepository {
         Ok(())
     }
 
-    pub async fn upsert(&self, id: &str, channel: Document) {
+    /// Updates or inserts a channel and returns whether it was inserted. How the
+    /// channel was found is only stored on insert, so known channels keep their history.
+    pub async fn upsert(
+        &self,
+        id: &str,
+        channel: Document,
+        discovery: Option<Document>,
+    ) -> Result<bool, Error> {
         let update_options = mongodb::options::UpdateOptions::builder()
             .upsert(true)
             .build();
 
-        self.collection
-            .update_one(doc! {"_id": id}, doc! {"$set": channel}, update_options)
-            .await
-            .unwrap();
+        let mut update = doc! {"$set": channel};
+
+        if let Some(discovery) = discovery {
+            update.insert("$setOnInsert", discovery);
+        }
+
+        let result = self
+            .collection
+            .update_one(doc! {"_id": id}, update, update_options)
+            .await?;
+
+        Ok(result.upserted_id.is_some())
     }
 
     pub async fn set_video_count_last_upload(
@@ -162,21 +370,265 @@ impl ChannelRepository {
             .unwrap();
     }
 
-    pub async fn set_scrape_error(&self, id: &str, error: String) {
+    pub async fn set_next_video_poll_at(
+        &self,
+        id: &str,
+        next_video_poll_at: chrono::DateTime<Utc>,
+    ) -> Result<(), Error> {
+        self.collection
+            .update_one(
+                doc! {"_id": id},
+                doc! {
+                    "$set": {
+                        "nextVideoPollAt": mongodb::bson::DateTime::from_millis(
+                            next_video_poll_at.timestamp_millis()
+                        )
+                    },
+                    "$unset": {"videoPollFailures": ""}
+                },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Schedules the next video feed poll after a failed one, keeping the failure count.
+    pub async fn set_video_poll_retry_at(
+        &self,
+        id: &str,
+        video_poll_retry_at: chrono::DateTime<Utc>,
+    ) -> Result<(), Error> {
         self.collection
             .update_one(
                 doc! {"_id": id},
                 doc! {
                     "$set": {
-                        "scrapeError": {
-                            "at": mongodb::bson::DateTime::now(),
-                            "error": error
-                        }
+                        "nextVideoPollAt": mongodb::bson::DateTime::from_millis(
+                            video_poll_retry_at.timestamp_millis()
+                        )
                     }
                 },
                 None,
             )
-            .await
-            .unwrap();
+            .await?;
+
+        Ok(())
+    }
+
+    /// Counts a failed video feed poll and returns the consecutive failures.
+    pub async fn add_video_poll_failure(&self, id: &str) -> Result<i32, Error> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .projection(doc! {"videoPollFailures": 1})
+            .build();
+
+        let channel = self
+            .collection
+            .find_one_and_update(
+                doc! {"_id": id},
+                doc! {"$inc": {"videoPollFailures": 1}},
+                options,
+            )
+            .await?;
+
+        let video_poll_failures = match channel {
+            Some(channel) => channel.get_i32("videoPollFailures").unwrap_or(0),
+            None => 0,
+        };
+
+        Ok(video_poll_failures)
+    }
+
+    pub async fn set_video_count(&self, id: &str, video_count: i64) -> Result<(), Error> {
+        self.collection
+            .update_one(
+                doc! {"_id": id},
+                doc! {"$set": {"videoCount": video_count}},
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_format_counts(&self, id: &str, format_counts: Document) -> Result<(), Error> {
+        self.collection
+            .update_one(
+                doc! {"_id": id},
+                doc! {"$set": {"formatCounts": format_counts}},
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_influence(&self, id: &str, influence: f64) -> Result<(), Error> {
+        self.collection
+            .update_one(
+                doc! {"_id": id},
+                doc! {
+                    "$set": {
+                        "influence": influence,
+                        "influenceUpdatedAt": mongodb::bson::DateTime::now()
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Counts a consecutive "not found" response and returns the new count. Returns
+    /// 0 for channels that are not stored.
+    pub async fn increment_not_found_count(&self, id: &str) -> Result<i32, Error> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .projection(doc! {"notFoundCount": 1})
+            .build();
+
+        let channel = self
+            .collection
+            .find_one_and_update(
+                doc! {"_id": id},
+                doc! {"$inc": {"notFoundCount": 1}},
+                options,
+            )
+            .await?;
+
+        let not_found_count = match channel {
+            Some(channel) => channel.get_i32("notFoundCount")?,
+            None => 0,
+        };
+
+        Ok(not_found_count)
+    }
+
+    pub async fn set_terminated(&self, id: &str) -> Result<(), Error> {
+        self.collection
+            .update_one(
+                doc! {"_id": id, "status": {"$ne": ChannelStatus::Terminated.as_str()}},
+                doc! {
+                    "$set": {
+                        "status": ChannelStatus::Terminated.as_str(),
+                        "terminatedAt": mongodb::bson::DateTime::now()
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Resets the "not found" tracking after the channel was loaded successfully.
+    pub async fn reset_not_found(&self, id: &str) -> Result<(), Error> {
+        self.collection
+            .update_one(
+                doc! {"_id": id, "notFoundCount": {"$exists": true}},
+                doc! {"$unset": {"notFoundCount": ""}},
+                None,
+            )
+            .await?;
+
+        self.collection
+            .update_one(
+                doc! {"_id": id, "status": ChannelStatus::Terminated.as_str()},
+                doc! {
+                    "$set": {"status": ChannelStatus::Active.as_str()},
+                    "$unset": {"terminatedAt": ""}
+                },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Appends an error to the bounded scrape error history and returns the number
+    /// of consecutive failures. Running out of quota is not counted as a failure.
+    /// Returns 0 for channels that are not stored.
+    pub async fn add_scrape_error(
+        &self,
+        id: &str,
+        error_type: ScrapeErrorType,
+        error: String,
+    ) -> Result<i32, Error> {
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .projection(doc! {"consecutiveFailures": 1})
+            .build();
+
+        let mut update = doc! {
+            "$push": {
+                "scrapeErrors": {
+                    "$each": [{
+                        "at": mongodb::bson::DateTime::now(),
+                        "type": error_type.as_str(),
+                        "error": error
+                    }],
+                    "$slice": -MAX_SCRAPE_ERRORS
+                }
+            }
+        };
+
+        if error_type != ScrapeErrorType::QuotaExceeded {
+            update.insert("$inc", doc! {"consecutiveFailures": 1});
+        }
+
+        let channel = self
+            .collection
+            .find_one_and_update(doc! {"_id": id}, update, options)
+            .await?;
+
+        let consecutive_failures = match channel {
+            Some(channel) => channel.get_i32("consecutiveFailures").unwrap_or(0),
+            None => 0,
+        };
+
+        Ok(consecutive_failures)
+    }
+
+    pub async fn set_next_scrape_at(
+        &self,
+        id: &str,
+        next_scrape_at: chrono::DateTime<Utc>,
+    ) -> Result<(), Error> {
+        self.collection
+            .update_one(
+                doc! {"_id": id},
+                doc! {
+                    "$set": {
+                        "nextScrapeAt": mongodb::bson::DateTime::from_millis(
+                            next_scrape_at.timestamp_millis()
+                        )
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn clear_scrape_errors(&self, id: &str) -> Result<(), Error> {
+        self.collection
+            .update_one(
+                doc! {"_id": id},
+                doc! {
+                    "$unset": {
+                        "scrapeError": "",
+                        "scrapeErrors": "",
+                        "consecutiveFailures": "",
+                        "nextScrapeAt": ""
+                    }
+                },
+                None,
+            )
+            .await?;
+
+        Ok(())
     }
 }
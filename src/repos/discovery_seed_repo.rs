@@ -0,0 +1,111 @@
+use anyhow::Error;
+use mongodb::bson::{doc, DateTime};
+use mongodb::options::UpdateOptions;
+use mongodb::{Client, Collection};
+
+use crate::models::discovery_seed::DiscoverySeed;
+use crate::utils::db::get_db_name;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionsStatus {
+    Usable,
+    Private,
+    Failed,
+    Skipped,
+}
+
+impl SubscriptionsStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubscriptionsStatus::Usable => "usable",
+            SubscriptionsStatus::Private => "private",
+            SubscriptionsStatus::Failed => "failed",
+            SubscriptionsStatus::Skipped => "skipped",
+        }
+    }
+}
+
+pub struct DiscoverySeedRepository {
+    collection: Collection<DiscoverySeed>,
+}
+
+impl DiscoverySeedRepository {
+    pub fn new(client: &Client, environment: &str) -> DiscoverySeedRepository {
+        let db = client.database(&get_db_name(environment));
+        let seeds = db.collection::<DiscoverySeed>("discoveryseeds");
+
+        DiscoverySeedRepository { collection: seeds }
+    }
+
+    pub async fn get(&self, channel_id: &str) -> Result<Option<DiscoverySeed>, Error> {
+        let seed = self
+            .collection
+            .find_one(doc! {"_id": channel_id}, None)
+            .await?;
+
+        Ok(seed)
+    }
+
+    pub async fn delete(&self, channel_id: &str) -> Result<(), Error> {
+        self.collection
+            .delete_one(doc! {"_id": channel_id}, None)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_subscriptions_etag(
+        &self,
+        channel_id: &str,
+        etag: Option<&str>,
+    ) -> Result<(), Error> {
+        let update_options = UpdateOptions::builder().upsert(true).build();
+
+        self.collection
+            .update_one(
+                doc! {"_id": channel_id},
+                doc! {
+                    "$set": {
+                        "subscriptionsEtag": etag,
+                        "subscriptionsStatus": SubscriptionsStatus::Usable.as_str(),
+                        "subscriptionsFetchedAt": DateTime::now(),
+                        "failures": 0,
+                    },
+                    "$unset": {
+                        "nextAttemptAt": "",
+                    }
+                },
+                update_options,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_subscriptions_failure(
+        &self,
+        channel_id: &str,
+        status: SubscriptionsStatus,
+        failures: i32,
+        next_attempt_at: i64,
+    ) -> Result<(), Error> {
+        let update_options = UpdateOptions::builder().upsert(true).build();
+
+        self.collection
+            .update_one(
+                doc! {"_id": channel_id},
+                doc! {
+                    "$set": {
+                        "subscriptionsStatus": status.as_str(),
+                        "subscriptionsFetchedAt": DateTime::now(),
+                        "failures": failures,
+                        "nextAttemptAt": next_attempt_at,
+                    }
+                },
+                update_options,
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,10 +1,14 @@
 pub mod additional_channel_repo;
 pub mod apikeys_repo;
 pub mod blacklist_repo;
+pub mod channel_edge_repo;
 pub mod channel_repo;
+pub mod discovery_seed_repo;
 pub mod non_sailing_channel_repo;
 pub mod sailing_term_repo;
 pub mod settings_repo;
 pub mod subscriber_repo;
+pub mod video_history_repo;
 pub mod video_repo;
+pub mod video_view_repo;
 pub mod view_repo;
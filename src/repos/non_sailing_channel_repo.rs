@@ -10,7 +10,7 @@ pub struct NonSailingChannelRepository {
 
 impl NonSailingChannelRepository {
     pub fn new(client: &Client, environment: &str) -> NonSailingChannelRepository {
-        let db = client.database(&get_db_name(&environment));
+        let db = client.database(&get_db_name(environment));
         let channels = db.collection::<Document>("nonsailingchannels");
 
         NonSailingChannelRepository {
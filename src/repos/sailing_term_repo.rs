@@ -11,7 +11,7 @@ pub struct SailingTermRepository {
 
 impl SailingTermRepository {
     pub fn new(client: &Client, environment: &str) -> SailingTermRepository {
-        let db = client.database(&get_db_name(&environment));
+        let db = client.database(&get_db_name(environment));
         let feeds = db.collection::<Document>("sailingterms");
 
         SailingTermRepository { collection: feeds }
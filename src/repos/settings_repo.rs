@@ -1,12 +1,15 @@
 use anyhow::Error;
 use chrono::Utc;
 use mongodb::{
-    bson::{doc, Document},
+    bson::{doc, from_document, to_document, Document},
     options::UpdateOptions,
     Client, Collection,
 };
 
-use crate::utils::{consts::ONE_DAYS_IN_SECONDS, db::get_db_name};
+use crate::{
+    models::discovery_run::DiscoveryRun,
+    utils::{consts::ONE_DAYS_IN_SECONDS, db::get_db_name},
+};
 
 pub struct SettingsRepository {
     collection: Collection<Document>,
@@ -14,7 +17,7 @@ pub struct SettingsRepository {
 
 impl SettingsRepository {
     pub fn new(client: &Client, environment: &str) -> SettingsRepository {
-        let db = client.database(&get_db_name(&environment));
+        let db = client.database(&get_db_name(environment));
         let settings = db.collection::<Document>("settings");
 
         SettingsRepository {
@@ -23,12 +26,86 @@ impl SettingsRepository {
     }
 
     pub async fn get_last_discovery_crawl(&self) -> Result<i64, Error> {
+        let default_value = Utc::now().timestamp() - ((ONE_DAYS_IN_SECONDS + 1) as i64);
+
+        self.get_i64("lastDiscoveryCrawl", default_value).await
+    }
+
+    pub async fn set_last_discovery_crawl(&self, last_crawl: i64) {
+        self.set_i64("lastDiscoveryCrawl", last_crawl).await;
+    }
+
+    pub async fn get_last_mention_discovery_crawl(&self) -> Result<i64, Error> {
+        self.get_i64("lastMentionDiscoveryCrawl", 0).await
+    }
+
+    pub async fn set_last_mention_discovery_crawl(&self, last_crawl: i64) {
+        self.set_i64("lastMentionDiscoveryCrawl", last_crawl).await;
+    }
+
+    pub async fn get_search_discovery_cursor(&self) -> Result<i64, Error> {
+        self.get_i64("searchDiscoveryCursor", 0).await
+    }
+
+    pub async fn set_search_discovery_cursor(&self, cursor: i64) {
+        self.set_i64("searchDiscoveryCursor", cursor).await;
+    }
+
+    pub async fn get_discovery_run(&self) -> Result<Option<DiscoveryRun>, Error> {
         let doc = self
             .collection
-            .find_one(doc! {"_id": "lastDiscoveryCrawl"}, None)
+            .find_one(doc! {"_id": "discoveryRun"}, None)
             .await?;
 
-        let default_value = Utc::now().timestamp() - ((ONE_DAYS_IN_SECONDS + 1) as i64);
+        match doc {
+            None => Ok(None),
+            Some(d) => {
+                let run = from_document::<DiscoveryRun>(d.get_document("value")?.clone())?;
+                Ok(Some(run))
+            }
+        }
+    }
+
+    pub async fn set_discovery_run(&self, run: &DiscoveryRun) -> Result<(), Error> {
+        let update = doc! {
+            "$set": {
+                "value": to_document(run)?,
+            }
+        };
+
+        let update_options = UpdateOptions::builder().upsert(true).build();
+
+        self.collection
+            .update_one(doc! {"_id": "discoveryRun"}, update, update_options)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Keeps the finished run as `lastDiscoveryRun` and removes the checkpoint.
+    pub async fn finish_discovery_run(&self, run: &DiscoveryRun) -> Result<(), Error> {
+        let update = doc! {
+            "$set": {
+                "value": to_document(run)?,
+                "finishedAt": Utc::now().timestamp(),
+            }
+        };
+
+        let update_options = UpdateOptions::builder().upsert(true).build();
+
+        self.collection
+            .update_one(doc! {"_id": "lastDiscoveryRun"}, update, update_options)
+            .await?;
+
+        self.collection
+            .delete_one(doc! {"_id": "discoveryRun"}, None)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_i64(&self, key: &str, default_value: i64) -> Result<i64, Error> {
+        let doc = self.collection.find_one(doc! {"_id": key}, None).await?;
 
         match doc {
             None => Ok(default_value),
@@ -39,17 +116,17 @@ impl SettingsRepository {
         }
     }
 
-    pub async fn set_last_discovery_crawl(&self, last_crawl: i64) {
+    async fn set_i64(&self, key: &str, value: i64) {
         let update = doc! {
             "$set": {
-                "value": last_crawl,
+                "value": value,
             }
         };
 
         let update_options = UpdateOptions::builder().upsert(true).build();
 
         self.collection
-            .update_one(doc! {"_id": "lastDiscoveryCrawl"}, update, update_options)
+            .update_one(doc! {"_id": key}, update, update_options)
             .await
             .unwrap();
     }
@@ -9,7 +9,7 @@ pub struct SubscriberRepository {
 
 impl SubscriberRepository {
     pub fn new(client: &Client, environment: &str) -> SubscriberRepository {
-        let db = client.database(&get_db_name(&environment));
+        let db = client.database(&get_db_name(environment));
         let channels = db.collection::<Document>("subscribers");
 
         SubscriberRepository {
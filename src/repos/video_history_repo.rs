@@ -0,0 +1,33 @@
+use mongodb::bson::{doc, Document};
+use mongodb::{Client, Collection};
+
+use crate::utils::db::get_db_name;
+
+pub struct VideoHistoryRepository {
+    collection: Collection<Document>,
+}
+
+impl VideoHistoryRepository {
+    pub fn new(client: &Client, environment: &str) -> VideoHistoryRepository {
+        let db = client.database(&get_db_name(environment));
+        let video_history = db.collection::<Document>("videohistory");
+
+        VideoHistoryRepository {
+            collection: video_history,
+        }
+    }
+
+    pub async fn delete_by_channel(&self, channel_id: &str) -> Result<(), anyhow::Error> {
+        self.collection
+            .delete_many(doc! {"channel": channel_id}, None)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn insert(&self, version: Document) -> Result<(), anyhow::Error> {
+        self.collection.insert_one(version, None).await?;
+
+        Ok(())
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::Error;
 use chrono::{TimeZone, Utc};
@@ -9,13 +9,28 @@ use mongodb::{Client, Collection};
 
 use crate::utils::db::get_db_name;
 
+/// The edit-tracked fields of a stored video.
+pub struct VideoVersion {
+    pub title: String,
+    pub description: String,
+    pub feed_updated_at: Option<i64>,
+}
+
+/// The description of a stored video, used to find mentioned channels.
+pub struct VideoDescription {
+    pub id: String,
+    pub channel: String,
+    pub description: String,
+    pub updated_at: i64,
+}
+
 pub struct VideoRepository {
     collection: Collection<Document>,
 }
 
 impl VideoRepository {
     pub fn new(client: &Client, environment: &str) -> VideoRepository {
-        let db = client.database(&get_db_name(&environment));
+        let db = client.database(&get_db_name(environment));
         let channels = db.collection::<Document>("videos");
 
         VideoRepository {
@@ -46,13 +61,195 @@ impl VideoRepository {
                 let id = doc.get_str("_id").unwrap().to_string();
                 let updated_at = doc.get_i64("updatedAt").unwrap();
 
-                (id, Utc.timestamp(updated_at as i64, 0))
+                (id, Utc.timestamp(updated_at, 0))
             })
             .collect::<HashMap<String, chrono::DateTime<Utc>>>();
 
         Ok(video_updated_lookup)
     }
 
+    pub async fn get_versions(
+        &self,
+        channel_id: &str,
+    ) -> Result<HashMap<String, VideoVersion>, Error> {
+        let find_options = FindOptions::builder()
+            .projection(doc! {
+                "_id": 1,
+                "title": 1,
+                "description": 1,
+                "feedUpdatedAt": 1
+            })
+            .build();
+
+        let cursor = self
+            .collection
+            .find(doc! {"channel": channel_id}, find_options)
+            .await?;
+        let videos: Vec<Document> = cursor.try_collect().await?;
+
+        let versions = videos
+            .iter()
+            .filter_map(|doc| {
+                let id = doc.get_str("_id").ok()?.to_string();
+                let version = VideoVersion {
+                    title: doc.get_str("title").unwrap_or_default().to_string(),
+                    description: doc.get_str("description").unwrap_or_default().to_string(),
+                    feed_updated_at: doc.get_i64("feedUpdatedAt").ok(),
+                };
+
+                Some((id, version))
+            })
+            .collect::<HashMap<String, VideoVersion>>();
+
+        Ok(versions)
+    }
+
+    pub async fn get_ids_by_channel(&self, channel_id: &str) -> Result<HashSet<String>, Error> {
+        let find_options = FindOptions::builder().projection(doc! {"_id": 1}).build();
+
+        let cursor = self
+            .collection
+            .find(doc! {"channel": channel_id}, find_options)
+            .await?;
+        let videos: Vec<Document> = cursor.try_collect().await?;
+
+        let video_ids = videos
+            .iter()
+            .filter_map(|doc| doc.get_str("_id").ok())
+            .map(|id| id.to_string())
+            .collect();
+
+        Ok(video_ids)
+    }
+
+    /// Returns ids of videos that were never enriched, followed by the videos
+    /// enriched longest ago.
+    pub async fn get_ids_for_enrichment(
+        &self,
+        enriched_before: chrono::DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<String>, Error> {
+        let find_options = FindOptions::builder()
+            .projection(doc! {"_id": 1})
+            .sort(doc! {"enrichedAt": 1})
+            .limit(limit)
+            .build();
+
+        let query = doc! {
+            "$or": [
+                {"enrichedAt": {"$exists": false}},
+                {"enrichedAt": {
+                    "$lt": mongodb::bson::DateTime::from_millis(enriched_before.timestamp_millis())
+                }}
+            ]
+        };
+
+        let cursor = self.collection.find(query, find_options).await?;
+        let videos: Vec<Document> = cursor.try_collect().await?;
+
+        let video_ids = videos
+            .iter()
+            .filter_map(|doc| doc.get_str("_id").ok())
+            .map(|id| id.to_string())
+            .collect();
+
+        Ok(video_ids)
+    }
+
+    pub async fn get_ids_with_format(
+        &self,
+        ids: &[String],
+        format: &str,
+    ) -> Result<HashSet<String>, Error> {
+        let find_options = FindOptions::builder().projection(doc! {"_id": 1}).build();
+
+        let cursor = self
+            .collection
+            .find(doc! {"_id": {"$in": ids}, "format": format}, find_options)
+            .await?;
+        let videos: Vec<Document> = cursor.try_collect().await?;
+
+        let video_ids = videos
+            .iter()
+            .filter_map(|doc| doc.get_str("_id").ok())
+            .map(|id| id.to_string())
+            .collect();
+
+        Ok(video_ids)
+    }
+
+    /// Returns a page of video descriptions, oldest update first. Pages continue after
+    /// the update timestamp and, within that second, after the id of the last video.
+    pub async fn get_descriptions_updated_after(
+        &self,
+        updated_after: i64,
+        after_id: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<VideoDescription>, Error> {
+        let find_options = FindOptions::builder()
+            .projection(doc! {
+                "channel" : 1,
+                "description" : 1,
+                "updatedAt" : 1
+            })
+            .sort(doc! {"updatedAt": 1, "_id": 1})
+            .limit(limit)
+            .build();
+
+        let query = match after_id {
+            Some(after_id) => doc! {
+                "$or": [
+                    {"updatedAt": {"$gt": updated_after}},
+                    {"updatedAt": updated_after, "_id": {"$gt": after_id}}
+                ]
+            },
+            None => doc! {"updatedAt": {"$gt": updated_after}},
+        };
+
+        let cursor = self.collection.find(query, find_options).await?;
+        let videos: Vec<Document> = cursor.try_collect().await?;
+
+        let descriptions = videos
+            .iter()
+            .filter_map(|doc| {
+                Some(VideoDescription {
+                    id: doc.get_str("_id").ok()?.to_string(),
+                    channel: doc.get_str("channel").ok()?.to_string(),
+                    description: doc.get_str("description").ok()?.to_string(),
+                    updated_at: doc.get_i64("updatedAt").ok()?,
+                })
+            })
+            .collect();
+
+        Ok(descriptions)
+    }
+
+    /// Returns the publish timestamps of the latest `limit` videos of a channel.
+    pub async fn get_latest_publish_timestamps(
+        &self,
+        channel_id: &str,
+        limit: i64,
+    ) -> Result<Vec<i64>, Error> {
+        let find_options = FindOptions::builder()
+            .projection(doc! {"publishedAt": 1})
+            .sort(doc! {"publishedAt": -1})
+            .limit(limit)
+            .build();
+
+        let cursor = self
+            .collection
+            .find(doc! {"channel": channel_id}, find_options)
+            .await?;
+        let videos: Vec<Document> = cursor.try_collect().await?;
+
+        let timestamps = videos
+            .iter()
+            .filter_map(|doc| doc.get_i64("publishedAt").ok())
+            .collect();
+
+        Ok(timestamps)
+    }
+
     pub async fn delete_all_by_channel(&self, channel_id: &str) -> Result<(), anyhow::Error> {
         self.collection
             .delete_many(doc! {"channel": channel_id}, None)
@@ -61,6 +258,62 @@ impl VideoRepository {
         Ok(())
     }
 
+    /// Stores details loaded from the API. A video that is returned by the API again
+    /// is no longer removed.
+    pub async fn set_details(&self, id: &str, details: Document) -> Result<(), Error> {
+        self.collection
+            .update_one(
+                doc! {"_id": id},
+                doc! {
+                    "$set": details,
+                    "$unset": {"removed": "", "removedAt": ""}
+                },
+                None,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Marks deleted or private videos as removed and returns the affected channels.
+    pub async fn set_removed(&self, ids: &[String]) -> Result<HashSet<String>, Error> {
+        let find_options = FindOptions::builder()
+            .projection(doc! {"channel": 1})
+            .build();
+
+        let cursor = self
+            .collection
+            .find(doc! {"_id": {"$in": ids}}, find_options)
+            .await?;
+        let videos: Vec<Document> = cursor.try_collect().await?;
+
+        let channel_ids = videos
+            .iter()
+            .filter_map(|doc| doc.get_str("channel").ok())
+            .map(|channel_id| channel_id.to_string())
+            .collect();
+
+        let now = mongodb::bson::DateTime::now();
+
+        self.collection
+            .update_many(
+                doc! {"_id": {"$in": ids}, "removed": {"$ne": true}},
+                doc! {"$set": {"removed": true, "removedAt": now}},
+                None,
+            )
+            .await?;
+
+        self.collection
+            .update_many(
+                doc! {"_id": {"$in": ids}},
+                doc! {"$set": {"enrichedAt": now}},
+                None,
+            )
+            .await?;
+
+        Ok(channel_ids)
+    }
+
     pub async fn upsert(&self, id: &str, video_doc: Document) -> Result<(), anyhow::Error> {
         let update_options = mongodb::options::UpdateOptions::builder()
             .upsert(true)
@@ -73,10 +326,38 @@ impl VideoRepository {
         Ok(())
     }
 
+    /// Counts the videos of a channel per format. Removed videos and videos without a
+    /// format are not counted.
+    pub async fn count_by_format(&self, channel_id: &str) -> Result<Vec<(String, i64)>, Error> {
+        let pipeline = vec![
+            doc! {"$match": {
+                "channel": channel_id,
+                "format": {"$exists": true},
+                "removed": {"$ne": true}
+            }},
+            doc! {"$group": {"_id": "$format", "count": {"$sum": 1}}},
+        ];
+
+        let cursor = self.collection.aggregate(pipeline, None).await?;
+        let groups: Vec<Document> = cursor.try_collect().await?;
+
+        let counts = groups
+            .iter()
+            .filter_map(|doc| {
+                let format = doc.get_str("_id").ok()?;
+                let count = doc.get_i32("count").ok()?;
+
+                Some((format.to_string(), count as i64))
+            })
+            .collect();
+
+        Ok(counts)
+    }
+
     pub async fn count(&self, channel_id: &str) -> Result<u64, anyhow::Error> {
         let count = self
             .collection
-            .count_documents(doc! {"channel": channel_id}, None)
+            .count_documents(doc! {"channel": channel_id, "removed": {"$ne": true}}, None)
             .await?;
 
         Ok(count)
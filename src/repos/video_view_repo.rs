@@ -0,0 +1,39 @@
+use mongodb::bson::{doc, Document};
+use mongodb::{Client, Collection};
+
+use crate::utils::db::get_db_name;
+
+pub struct VideoViewRepository {
+    collection: Collection<Document>,
+}
+
+impl VideoViewRepository {
+    pub fn new(client: &Client, environment: &str) -> VideoViewRepository {
+        let db = client.database(&get_db_name(environment));
+        let video_views = db.collection::<Document>("videoviews");
+
+        VideoViewRepository {
+            collection: video_views,
+        }
+    }
+
+    pub async fn delete_by_channel(&self, channel_id: &str) -> Result<(), anyhow::Error> {
+        self.collection
+            .delete_many(doc! {"channel": channel_id}, None)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn upsert(&self, id: Document, view: Document) -> Result<(), anyhow::Error> {
+        let update_options = mongodb::options::UpdateOptions::builder()
+            .upsert(true)
+            .build();
+
+        self.collection
+            .update_one(doc! {"_id": id}, doc! {"$set": view}, update_options)
+            .await?;
+
+        Ok(())
+    }
+}
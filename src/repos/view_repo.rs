@@ -9,7 +9,7 @@ pub struct ViewRepository {
 
 impl ViewRepository {
     pub fn new(client: &Client, environment: &str) -> ViewRepository {
-        let db = client.database(&get_db_name(&environment));
+        let db = client.database(&get_db_name(environment));
         let channels = db.collection::<Document>("views");
 
         ViewRepository {
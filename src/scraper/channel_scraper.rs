@@ -1,66 +1,127 @@
 use anyhow::Error;
 use chrono::{DateTime, Datelike, Utc};
 use log::{error, info, warn};
-use mongodb::bson::doc;
+use mongodb::bson::{doc, Document};
 use whatlang::detect;
 
+use tokio::sync::mpsc::Sender;
+
 use crate::{
-    models::youtube_channel_details::YoutubeStatisticsItem,
+    commands::{crawl_channel_command::Discovery, crawl_videos_command::CrawlVideosCommand},
+    models::{
+        config::ChannelConfig, youtube_channel_details::YoutubeStatisticsItem,
+        youtube_error_response::YoutubeApiError,
+    },
     repos::{
-        apikeys_repo::ApiKeyRepository, channel_repo::ChannelRepository,
-        subscriber_repo::SubscriberRepository, video_repo::VideoRepository,
+        apikeys_repo::ApiKeyRepository,
+        channel_repo::{ChannelRepository, ScrapeErrorType},
+        subscriber_repo::SubscriberRepository,
+        video_history_repo::VideoHistoryRepository,
+        video_repo::VideoRepository,
+        video_view_repo::VideoViewRepository,
         view_repo::ViewRepository,
     },
-    services::{sailing_terms_service::SailingTermsService, youtube_service::YoutubeService},
-    utils::keyword_utils,
+    services::{
+        sailing_terms_service::SailingTermsService,
+        youtube_service::{ChannelNotFoundError, YoutubeService},
+    },
+    utils::{backoff_utils::get_backoff_seconds, keyword_utils},
 };
 
+const QUOTA_EXCEEDED: &str = "quotaExceeded";
+const MAX_BACKOFF_DAYS: i64 = 32;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScrapeOutcome {
+    Accepted,
+    Rejected(String),
+    Failed(String),
+}
+
 pub struct ChannelScraper {
     channel_repo: ChannelRepository,
     view_repo: ViewRepository,
     subscriber_repo: SubscriberRepository,
     video_repo: VideoRepository,
+    video_view_repo: VideoViewRepository,
+    video_history_repo: VideoHistoryRepository,
     youtube_service: YoutubeService,
     sailing_terms_service: SailingTermsService,
+    video_sender: Sender<CrawlVideosCommand>,
+    config: ChannelConfig,
 }
 
 impl ChannelScraper {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         channel_repo: ChannelRepository,
         view_repo: ViewRepository,
         subscriber_repo: SubscriberRepository,
         video_repo: VideoRepository,
+        video_view_repo: VideoViewRepository,
+        video_history_repo: VideoHistoryRepository,
         apikey_repo: ApiKeyRepository,
         sailing_terms_service: SailingTermsService,
+        video_sender: Sender<CrawlVideosCommand>,
+        config: ChannelConfig,
     ) -> ChannelScraper {
         ChannelScraper {
             channel_repo,
             view_repo,
             subscriber_repo,
             video_repo,
+            video_view_repo,
+            video_history_repo,
             youtube_service: YoutubeService::new(apikey_repo),
             sailing_terms_service,
+            video_sender,
+            config,
         }
     }
 
+    /// Scrapes a channel. Errors while scraping are stored with the channel and
+    /// reported as `ScrapeOutcome::Failed`.
     pub async fn scrape(
         &self,
         channel_id: String,
         ignore_sailing_terms: bool,
-    ) -> Result<(), Error> {
+        discovery: Option<Discovery>,
+    ) -> Result<ScrapeOutcome, Error> {
         info!("Start scraping channel {}", channel_id);
 
-        let channel_details = match self.load_channel_details(&channel_id).await {
-            Ok(value) => value,
-            Err(value) => return value,
-        };
+        match self
+            .scrape_channel(&channel_id, ignore_sailing_terms, discovery)
+            .await
+        {
+            Ok(outcome) => {
+                self.channel_repo.clear_scrape_errors(&channel_id).await?;
+
+                Ok(outcome)
+            }
+            Err(err) => {
+                error!("Failed to scrape channel {}: {}", channel_id, err);
+
+                self.store_scrape_error(&channel_id, &err).await?;
+
+                Ok(ScrapeOutcome::Failed(err.to_string()))
+            }
+        }
+    }
+
+    async fn scrape_channel(
+        &self,
+        channel_id: &str,
+        ignore_sailing_terms: bool,
+        discovery: Option<Discovery>,
+    ) -> Result<ScrapeOutcome, Error> {
+        let channel_details = self.load_channel_details(channel_id).await?;
 
         let description = channel_details.snippet.description.unwrap_or_default();
 
         let sailing_term_result = self
             .sailing_terms_service
             .has_sailing_term(
-                &channel_id,
+                channel_id,
                 &channel_details.snippet.title,
                 &description,
                 ignore_sailing_terms,
@@ -68,7 +129,11 @@ impl ChannelScraper {
             .await;
 
         if sailing_term_result.is_blacklisted {
-            self.delete_channel(&channel_id).await?;
+            self.delete_channel(channel_id).await?;
+
+            return Ok(ScrapeOutcome::Rejected(
+                "Channel is blacklisted".to_string(),
+            ));
         }
 
         let view_count = channel_details
@@ -77,8 +142,14 @@ impl ChannelScraper {
             .parse::<i64>()
             .unwrap_or(0);
 
-        if sailing_term_result.has_sailing_term == false || view_count == 0 {
-            return Ok(());
+        if !sailing_term_result.has_sailing_term {
+            return Ok(ScrapeOutcome::Rejected(
+                "Channel does not mention any sailing terms".to_string(),
+            ));
+        }
+
+        if view_count == 0 {
+            return Ok(ScrapeOutcome::Rejected("Channel has no views".to_string()));
         }
 
         let subscriber_count = match channel_details.statistics.subscriber_count {
@@ -100,11 +171,8 @@ impl ChannelScraper {
             "lastCrawl": mongodb::bson::DateTime::now(),
         };
 
-        if channel_details.snippet.country.is_some() {
-            channel.insert(
-                "country",
-                channel_details.snippet.country.unwrap().to_lowercase(),
-            );
+        if let Some(country) = channel_details.snippet.country {
+            channel.insert("country", country.to_lowercase());
         }
 
         let keywords = keyword_utils::parse_keywords(
@@ -115,54 +183,109 @@ impl ChannelScraper {
                 .unwrap_or_default(),
         );
 
-        if keywords.len() > 0 {
+        if !keywords.is_empty() {
             channel.insert("keywords", keywords);
         }
 
-        let language_option = self.detect_language(&channel_id, &description).await;
-        match language_option {
-            Some(language) => {
-                channel.insert("language", language);
-                channel.insert("detectedLanguage", true);
-            }
-            None => {}
+        let language_option = self.detect_language(channel_id, &description).await;
+        if let Some(language) = language_option {
+            channel.insert("language", language);
+            channel.insert("detectedLanguage", true);
         }
 
-        self.store_view_count(&channel_id, view_count).await;
-        self.store_subscriber_count(&channel_id, subscriber_count)
+        self.store_view_count(channel_id, view_count).await;
+        self.store_subscriber_count(channel_id, subscriber_count)
             .await;
 
-        self.channel_repo.upsert(&channel_id, channel).await;
+        let is_new_channel = self
+            .channel_repo
+            .upsert(channel_id, channel, discovery.map(build_discovery_document))
+            .await?;
 
-        Ok(())
+        if is_new_channel {
+            info!("Backfill upload history of new channel {}", channel_id);
+
+            let command = CrawlVideosCommand {
+                channel_id: channel_id.to_string(),
+                backfill: true,
+            };
+
+            self.video_sender.send(command).await?;
+        }
+
+        Ok(ScrapeOutcome::Accepted)
     }
 
-    async fn load_channel_details(
-        &self,
-        channel_id: &String,
-    ) -> Result<YoutubeStatisticsItem, Result<(), Error>> {
+    async fn load_channel_details(&self, channel_id: &str) -> Result<YoutubeStatisticsItem, Error> {
         let channel_details_result = self.youtube_service.get_channel_details(channel_id).await;
         let channel_details = match channel_details_result {
             Ok(channel_details) => channel_details,
             Err(err) => {
-                error!("Failed to get channel details for {}: {}", channel_id, err);
-
-                self.channel_repo
-                    .set_scrape_error(channel_id, err.to_string())
-                    .await;
+                if err.downcast_ref::<ChannelNotFoundError>().is_some() {
+                    self.track_not_found(channel_id).await?;
+                }
 
-                return Err(Ok(()));
+                return Err(err);
             }
         };
 
+        self.channel_repo.reset_not_found(channel_id).await?;
+
         Ok(channel_details)
     }
 
+    async fn store_scrape_error(&self, channel_id: &str, err: &Error) -> Result<(), Error> {
+        let error_type = get_scrape_error_type(err);
+        let consecutive_failures = self
+            .channel_repo
+            .add_scrape_error(channel_id, error_type, err.to_string())
+            .await?;
+
+        // Running out of quota is not the channel's fault, so it is retried as usual.
+        if consecutive_failures == 0 || error_type == ScrapeErrorType::QuotaExceeded {
+            return Ok(());
+        }
+
+        let backoff_seconds = get_backoff_seconds(consecutive_failures, MAX_BACKOFF_DAYS);
+        let next_scrape_at = Utc::now() + chrono::Duration::seconds(backoff_seconds);
+
+        warn!(
+            "Channel {} failed {} times in a row, retry after {}",
+            channel_id, consecutive_failures, next_scrape_at
+        );
+
+        self.channel_repo
+            .set_next_scrape_at(channel_id, next_scrape_at)
+            .await
+    }
+
+    async fn track_not_found(&self, channel_id: &str) -> Result<(), Error> {
+        let not_found_count = self
+            .channel_repo
+            .increment_not_found_count(channel_id)
+            .await?;
+
+        if not_found_count >= self.config.not_found_threshold {
+            warn!(
+                "Channel {} was not found {} times in a row, mark as terminated",
+                channel_id, not_found_count
+            );
+
+            self.channel_repo.set_terminated(channel_id).await?;
+        }
+
+        Ok(())
+    }
+
     async fn delete_channel(&self, channel_id: &str) -> Result<(), Error> {
         self.channel_repo.delete(channel_id).await?;
         self.view_repo.delete_by_channel(channel_id).await?;
         self.subscriber_repo.delete_by_channel(channel_id).await?;
         self.video_repo.delete_all_by_channel(channel_id).await?;
+        self.video_view_repo.delete_by_channel(channel_id).await?;
+        self.video_history_repo
+            .delete_by_channel(channel_id)
+            .await?;
 
         Ok(())
     }
@@ -176,7 +299,7 @@ impl ChannelScraper {
             "tr",
         ];
 
-        if language_detected == false && text.len() > 0 {
+        if !language_detected && !text.is_empty() {
             match detect(text) {
                 Some(language) => {
                     let lang_code = &language.lang().code()[..2];
@@ -212,7 +335,7 @@ impl ChannelScraper {
                     "month": now.month(),
                     "day": now.day(),
                     "date": mongodb::bson::DateTime::from_millis(
-                        now.timestamp_millis() as i64
+                        now.timestamp_millis()
                     ),
                     "views": view_count
                 },
@@ -235,7 +358,7 @@ impl ChannelScraper {
                     "month": now.month(),
                     "day": now.day(),
                     "date": mongodb::bson::DateTime::from_millis(
-                        now.timestamp_millis() as i64
+                        now.timestamp_millis()
                     ),
                     "subscribers": subscriber_count
                 },
@@ -244,3 +367,40 @@ impl ChannelScraper {
             .expect("Failed to upsert view count");
     }
 }
+
+fn get_scrape_error_type(err: &Error) -> ScrapeErrorType {
+    if err.downcast_ref::<ChannelNotFoundError>().is_some() {
+        return ScrapeErrorType::NotFound;
+    }
+
+    if let Some(api_error) = err.downcast_ref::<YoutubeApiError>() {
+        if api_error.has_reason(QUOTA_EXCEEDED) {
+            return ScrapeErrorType::QuotaExceeded;
+        }
+
+        return ScrapeErrorType::Api;
+    }
+
+    if err.downcast_ref::<reqwest::Error>().is_some() {
+        return ScrapeErrorType::Network;
+    }
+
+    ScrapeErrorType::Other
+}
+
+fn build_discovery_document(discovery: Discovery) -> Document {
+    let mut discovery_doc = doc! {
+        "discoveredVia": discovery.source.to_document(),
+        "discoveredAt": mongodb::bson::DateTime::now(),
+    };
+
+    if let Some(depth) = discovery.depth {
+        discovery_doc.insert("discoveryDepth", depth);
+    }
+
+    if let Some(run_id) = discovery.run_id {
+        discovery_doc.insert("discoveryRunId", run_id);
+    }
+
+    discovery_doc
+}
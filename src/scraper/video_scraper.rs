@@ -1,37 +1,82 @@
 use std::collections::HashMap;
 
 use anyhow::{anyhow, Error};
-use chrono::{DateTime, FixedOffset, Utc};
-use log::info;
-use mongodb::bson::{doc, Document};
-use quick_xml::de::from_str;
+use chrono::{DateTime, Datelike, FixedOffset, Utc};
+use log::{info, warn};
+use mongodb::bson::{doc, Bson, Document};
 
 use crate::{
-    models::youtube_video_feed_response::{Entry, YoutubeVideoFeedResponse},
-    repos::{channel_repo::ChannelRepository, video_repo::VideoRepository},
+    models::{
+        config::VideoConfig,
+        youtube_video_feed_response::{Entry, YoutubeVideoFeedResponse},
+    },
+    repos::{
+        channel_repo::ChannelRepository,
+        video_history_repo::VideoHistoryRepository,
+        video_repo::{VideoRepository, VideoVersion},
+        video_view_repo::VideoViewRepository,
+    },
+    services::youtube_service::YoutubeService,
+    utils::{
+        consts::DORMANT_AFTER_WEEKS,
+        polling_utils,
+        video_feed_parser::parse_video_feed,
+        video_format_utils::{is_short_url, VideoFormat},
+        video_history_utils::get_changed_fields,
+    },
 };
 
 const YOUTUBE_VIDEO_FEED_BASE_URL: &str = "https://www.youtube.com/feeds/videos.xml";
 const ONE_HOUR_IN_SECONDS: i64 = 3600;
 const ONE_DAY_IN_SECONDS: i64 = 86400;
 const ONE_WEEK_IN_SECONDS: i64 = 604800;
+const POLL_CADENCE_VIDEO_COUNT: i64 = 20;
+const FEED_ENTRY_COUNT: usize = 15;
 
 pub struct VideoScraper {
     video_repo: VideoRepository,
     channel_repo: ChannelRepository,
+    video_view_repo: VideoViewRepository,
+    video_history_repo: VideoHistoryRepository,
+    youtube_service: YoutubeService,
+    config: VideoConfig,
 }
 
 impl VideoScraper {
-    pub fn new(video_repo: VideoRepository, channel_repo: ChannelRepository) -> Self {
+    pub fn new(
+        video_repo: VideoRepository,
+        channel_repo: ChannelRepository,
+        video_view_repo: VideoViewRepository,
+        video_history_repo: VideoHistoryRepository,
+        youtube_service: YoutubeService,
+        config: VideoConfig,
+    ) -> Self {
         Self {
             video_repo,
             channel_repo,
+            video_view_repo,
+            video_history_repo,
+            youtube_service,
+            config,
         }
     }
 
-    pub async fn scrape(&self, channel_id: String) -> Result<(), Error> {
-        let channel_feed = load_and_parse_video_feed(&channel_id).await?;
+    pub async fn scrape(&self, channel_id: String, backfill: bool) -> Result<(), Error> {
+        let channel_feed = match load_and_parse_video_feed(&channel_id).await {
+            Ok(channel_feed) => channel_feed,
+            Err(e) => {
+                self.schedule_retry_poll(&channel_id).await?;
+                return Err(e);
+            }
+        };
         let updated_lookup = self.video_repo.get_updated_lookup(&channel_id).await?;
+        let versions = self.video_repo.get_versions(&channel_id).await?;
+
+        if backfill || has_feed_gap(&updated_lookup, &channel_feed.entries) {
+            if let Err(e) = self.backfill(&channel_id).await {
+                warn!("Failed to backfill videos of channel {}: {}", channel_id, e);
+            }
+        }
 
         let mut max_last_upload_timestamp: i64 = 0;
 
@@ -41,12 +86,23 @@ impl VideoScraper {
                 max_last_upload_timestamp = published.timestamp();
             }
 
-            let should_update = should_update_video(&updated_lookup, entry, published);
+            self.store_video_view_count(&channel_id, entry).await?;
+
+            let updated = DateTime::parse_from_rfc3339(&entry.updated)?;
+            let edited = match versions.get(&entry.video_id) {
+                Some(version) => {
+                    self.store_prior_version(&channel_id, entry, version, updated)
+                        .await?
+                }
+                None => false,
+            };
+
+            let should_update = edited || should_update_video(&updated_lookup, entry, published);
             if !should_update {
                 continue;
             }
 
-            let vid = self.build_video_document(&channel_id, &entry, published);
+            let vid = self.build_video_document(&channel_id, entry, published, updated);
 
             info!("Updating video {}", entry.video_id);
             self.video_repo.upsert(&entry.video_id, vid).await?;
@@ -55,25 +111,211 @@ impl VideoScraper {
         self.update_channel_video_stats(&channel_id, max_last_upload_timestamp)
             .await?;
 
+        let dormant_before = Utc::now() - chrono::Duration::weeks(DORMANT_AFTER_WEEKS);
+        if max_last_upload_timestamp >= dormant_before.timestamp()
+            && self.channel_repo.revive_if_dormant(&channel_id).await?
+        {
+            info!("Dormant channel {} uploaded again", channel_id);
+        }
+
+        self.schedule_next_poll(&channel_id).await?;
+
         Ok(())
     }
 
+    async fn schedule_next_poll(&self, channel_id: &str) -> Result<(), Error> {
+        let publish_timestamps = self
+            .video_repo
+            .get_latest_publish_timestamps(channel_id, POLL_CADENCE_VIDEO_COUNT)
+            .await?;
+
+        let now = Utc::now();
+        let interval = polling_utils::get_next_poll_interval(
+            &publish_timestamps,
+            now.timestamp(),
+            self.config.min_poll_interval_hours * ONE_HOUR_IN_SECONDS,
+            self.config.max_poll_interval_hours * ONE_HOUR_IN_SECONDS,
+        );
+
+        self.channel_repo
+            .set_next_video_poll_at(channel_id, now + chrono::Duration::seconds(interval))
+            .await
+    }
+
+    /// Backs off polling a channel whose feed keeps failing to load or parse,
+    /// so it is not picked up again on every run of the new video crawler.
+    async fn schedule_retry_poll(&self, channel_id: &str) -> Result<(), Error> {
+        let failures = self.channel_repo.add_video_poll_failure(channel_id).await?;
+        let interval = polling_utils::get_failure_backoff_interval(
+            failures,
+            self.config.min_poll_interval_hours * ONE_HOUR_IN_SECONDS,
+            self.config.max_poll_interval_hours * ONE_HOUR_IN_SECONDS,
+        );
+
+        self.channel_repo
+            .set_video_poll_retry_at(channel_id, Utc::now() + chrono::Duration::seconds(interval))
+            .await
+    }
+
+    /// Imports all videos from the uploads playlist of a channel that are not
+    /// stored yet. Pages are loaded newest first until a page holds only stored
+    /// videos or `backfill_max_pages` is reached.
+    async fn backfill(&self, channel_id: &str) -> Result<(), Error> {
+        let stored_video_ids = self.video_repo.get_ids_by_channel(channel_id).await?;
+        let mut page_token: Option<String> = None;
+        let mut pages = 0;
+        let mut imported_count = 0;
+
+        while pages < self.config.backfill_max_pages {
+            pages += 1;
+
+            let uploads = self
+                .youtube_service
+                .get_uploads_page(channel_id, page_token)
+                .await?;
+            let mut has_unknown_video = false;
+
+            for upload in uploads.items {
+                let video_id = upload.content_details.video_id;
+
+                if stored_video_ids.contains(&video_id) {
+                    continue;
+                }
+
+                has_unknown_video = true;
+
+                // Private and deleted videos have no publish date.
+                let video_published_at = match upload.content_details.video_published_at {
+                    Some(video_published_at) => video_published_at,
+                    None => continue,
+                };
+
+                let published = DateTime::parse_from_rfc3339(&video_published_at)?;
+                let vid = doc! {
+                    "_id": video_id.clone(),
+                    "title": upload.snippet.title,
+                    "description": upload.snippet.description,
+                    "publishedAt": published.timestamp(),
+                    "updatedAt": Utc::now().timestamp(),
+                    "channel": channel_id,
+                };
+
+                self.video_repo.upsert(&video_id, vid).await?;
+                imported_count += 1;
+            }
+
+            // Older pages only hold videos that were stored before.
+            if !has_unknown_video {
+                break;
+            }
+
+            page_token = uploads.next_page_token;
+
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        info!(
+            "Imported {} videos of channel {} from {} pages of the uploads playlist",
+            imported_count, channel_id, pages
+        );
+
+        Ok(())
+    }
+
+    async fn store_video_view_count(&self, channel_id: &str, entry: &Entry) -> Result<(), Error> {
+        let statistics = match &entry.group.community.statistics {
+            Some(statistics) => statistics,
+            None => return Ok(()),
+        };
+
+        let now = Utc::now();
+
+        self.video_view_repo
+            .upsert(
+                doc! {
+                    "video": entry.video_id.clone(),
+                    "date": now.format("%Y%m%d").to_string().parse::<i32>()?,
+                },
+                doc! {
+                    "channel": channel_id,
+                    "year": now.year(),
+                    "month": now.month(),
+                    "day": now.day(),
+                    "date": mongodb::bson::DateTime::from_millis(now.timestamp_millis()),
+                    "views": statistics.views,
+                },
+            )
+            .await
+    }
+
+    /// Stores the stored title and description of a video as a prior
+    /// version if the feed entry differs from it. Returns whether the video was edited.
+    async fn store_prior_version(
+        &self,
+        channel_id: &str,
+        entry: &Entry,
+        version: &VideoVersion,
+        updated: DateTime<FixedOffset>,
+    ) -> Result<bool, Error> {
+        if version.feed_updated_at == Some(updated.timestamp()) {
+            return Ok(false);
+        }
+
+        let changed_fields = get_changed_fields(version, entry);
+
+        if changed_fields.is_empty() {
+            return Ok(false);
+        }
+
+        info!(
+            "Video {} changed {}",
+            entry.video_id,
+            changed_fields.join(", ")
+        );
+
+        self.video_history_repo
+            .insert(doc! {
+                "video": entry.video_id.clone(),
+                "channel": channel_id,
+                "title": version.title.clone(),
+                "description": version.description.clone(),
+                "changedFields": changed_fields,
+                "replacedAt": mongodb::bson::DateTime::from_millis(updated.timestamp_millis()),
+                "recordedAt": mongodb::bson::DateTime::now(),
+            })
+            .await?;
+
+        Ok(true)
+    }
+
     async fn update_channel_video_stats(
         &self,
         channel_id: &str,
         max_last_upload_timestamp: i64,
     ) -> Result<(), Error> {
-        let videos_per_channel = self.video_repo.count(&channel_id).await?;
+        let videos_per_channel = self.video_repo.count(channel_id).await?;
 
         self.channel_repo
             .set_video_count_last_upload(
-                &channel_id,
+                channel_id,
                 videos_per_channel as i64,
                 max_last_upload_timestamp,
             )
             .await;
 
-        Ok(())
+        let format_counts = self
+            .video_repo
+            .count_by_format(channel_id)
+            .await?
+            .into_iter()
+            .map(|(format, count)| (format, Bson::Int64(count)))
+            .collect::<Document>();
+
+        self.channel_repo
+            .set_format_counts(channel_id, format_counts)
+            .await
     }
 
     fn build_video_document(
@@ -81,21 +323,75 @@ impl VideoScraper {
         channel_id: &str,
         entry: &Entry,
         published: DateTime<FixedOffset>,
+        updated: DateTime<FixedOffset>,
     ) -> Document {
-        let vid = doc! {
+        let mut vid = doc! {
             "_id": entry.video_id.clone(),
             "title": entry.title.clone(),
             "description": entry.group.description.clone(),
             "publishedAt": published.timestamp(),
             "updatedAt": Utc::now().timestamp(),
-            "views": entry.group.community.statistics.views,
-            "channel": channel_id.clone(),
+            "feedUpdatedAt": updated.timestamp(),
+            "channel": channel_id,
         };
 
+        if let Some(statistics) = &entry.group.community.statistics {
+            vid.insert("views", statistics.views);
+        }
+
+        if let Some(thumbnail) = &entry.group.thumbnail {
+            vid.insert(
+                "thumbnail",
+                doc! {
+                    "url": thumbnail.url.clone(),
+                    "width": thumbnail.width,
+                    "height": thumbnail.height,
+                },
+            );
+        }
+
+        if let Some(star_rating) = &entry.group.community.star_rating {
+            vid.insert(
+                "starRating",
+                doc! {
+                    "count": star_rating.count,
+                    "average": star_rating.average,
+                },
+            );
+        }
+
+        if let Some(link) = &entry.link {
+            vid.insert("link", link.href.clone());
+
+            if is_short_url(&link.href) {
+                vid.insert("format", VideoFormat::Short.as_str());
+            }
+        }
+
+        if let Some(author) = &entry.author {
+            vid.insert(
+                "author",
+                doc! {
+                    "name": author.name.clone(),
+                    "uri": author.uri.clone(),
+                },
+            );
+        }
+
         vid
     }
 }
 
+/// A full feed without any known video hints at more uploads since the last poll
+/// than the feed contains.
+fn has_feed_gap(updated_lookup: &HashMap<String, DateTime<Utc>>, entries: &[Entry]) -> bool {
+    !updated_lookup.is_empty()
+        && entries.len() >= FEED_ENTRY_COUNT
+        && entries
+            .iter()
+            .all(|entry| !updated_lookup.contains_key(&entry.video_id))
+}
+
 fn should_update_video(
     updated_lookup: &HashMap<String, DateTime<Utc>>,
     entry: &Entry,
@@ -121,9 +417,8 @@ fn should_update_video(
 
         let updated_at = updated_lookup.get(&entry.video_id).unwrap();
         let updated_time_diff = (Utc::now().timestamp() - updated_at.timestamp()).abs();
-        let should_update_video = updated_time_diff >= uploaded_later_than_threshold;
 
-        should_update_video
+        updated_time_diff >= uploaded_later_than_threshold
     };
 
     should_update
@@ -142,17 +437,10 @@ async fn load_and_parse_video_feed(channel_id: &str) -> Result<YoutubeVideoFeedR
         ));
     }
 
-    let xml = response
-        .text()
-        .await?
-        .replace("yt:", "yt")
-        .replace("media:", "media");
-
-    let channel_feed = from_str::<YoutubeVideoFeedResponse>(&xml).expect(&format!(
-        "{}, xml string length {}",
-        &feed_url,
-        xml.len()
-    ));
+    let xml = response.text().await?;
+
+    let channel_feed = parse_video_feed(&xml)
+        .map_err(|e| anyhow!("Failed to parse video feed {}: {}", feed_url, e))?;
 
     Ok(channel_feed)
 }
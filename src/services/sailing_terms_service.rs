@@ -24,6 +24,10 @@ impl SailingTermsService {
         }
     }
 
+    pub fn sailing_terms(&self) -> &[String] {
+        &self.sailing_terms
+    }
+
     pub async fn is_not_listed_as_non_sailing_channel(&self, channel_id: &str) -> bool {
         let non_sailing_channel_exists = self
             .non_sailing_channel_repo
@@ -53,11 +57,11 @@ impl SailingTermsService {
             }
         }
 
-        if has_sailing_term == false && ignore_sailing_terms == false {
-            self.non_sailing_channel_repo.upsert(&channel_id).await;
+        if !has_sailing_term && !ignore_sailing_terms {
+            self.non_sailing_channel_repo.upsert(channel_id).await;
         }
 
-        if ignore_sailing_terms == true {
+        if ignore_sailing_terms {
             has_sailing_term = true;
         }
 
@@ -1,24 +1,85 @@
-use anyhow::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+use anyhow::{anyhow, Error};
+use chrono::{DateTime, SecondsFormat, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use reqwest::{header::IF_NONE_MATCH, redirect::Policy, IntoUrl, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
 
 use crate::{
     models::{
+        apikey::ApiKey,
         youtube_channel_details::{YouTubeChannelDetails, YoutubeStatisticsItem},
+        youtube_channel_sections::YoutubeChannelSections,
         youtube_channel_subscriptions::{
             YouTubeChannelSubscriptionSnippet, YoutubeChannelSubscriptions,
         },
+        youtube_error_response::YoutubeErrorResponse,
+        youtube_id_list::YoutubeIdList,
+        youtube_playlist_items::YoutubePlaylistItems,
+        youtube_search_results::YoutubeSearchResults,
+        youtube_video_details::{YoutubeVideoDetails, YoutubeVideoItem},
     },
     repos::apikeys_repo::ApiKeyRepository,
+    utils::channel_reference_utils::{is_valid_channel_id, ChannelReference},
 };
 
 const BASE_URL: &str = "https://www.googleapis.com/youtube/v3/";
+const YOUTUBE_BASE_URL: &str = "https://www.youtube.com/";
+pub const MAX_IDS_PER_REQUEST: usize = 50;
+const DEFAULT_QUOTA_COST: i32 = 1;
+pub const SEARCH_QUOTA_COST: i32 = 100;
+
+static CANONICAL_CHANNEL_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"<link rel="canonical" href="https://www\.youtube\.com/channel/(UC[0-9A-Za-z_-]{22})">"#,
+    )
+    .unwrap()
+});
+
+pub struct SubscriptionList {
+    pub etag: Option<String>,
+    pub snippets: Vec<YouTubeChannelSubscriptionSnippet>,
+}
+
+/// The API answered successfully but did not return the requested channel, which
+/// happens for terminated, deleted or mistyped channels.
+#[derive(Debug)]
+pub struct ChannelNotFoundError {
+    pub channel_id: String,
+}
+
+impl fmt::Display for ChannelNotFoundError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Channel {} not found", self.channel_id)
+    }
+}
+
+impl std::error::Error for ChannelNotFoundError {}
 
 pub struct YoutubeService {
     apikey_repo: ApiKeyRepository,
+    used_quota: AtomicI64,
+    no_redirect_client: reqwest::Client,
 }
 
 impl YoutubeService {
     pub fn new(apikey_repo: ApiKeyRepository) -> YoutubeService {
-        YoutubeService { apikey_repo }
+        YoutubeService {
+            apikey_repo,
+            used_quota: AtomicI64::new(0),
+            no_redirect_client: reqwest::Client::builder()
+                .redirect(Policy::none())
+                .build()
+                .expect("Failed to build http client"),
+        }
+    }
+
+    /// Quota units spent by this service instance since it was created.
+    pub fn used_quota(&self) -> i64 {
+        self.used_quota.load(Ordering::Relaxed)
     }
 
     pub async fn get_channel_details(
@@ -32,30 +93,277 @@ impl YoutubeService {
             BASE_URL, channel_id, api_key.key
         );
 
-        let resp = reqwest::get(url)
+        let resp = self
+            .get_json::<YouTubeChannelDetails>(url, &api_key, DEFAULT_QUOTA_COST)
+            .await?;
+
+        match resp.items.and_then(|items| items.into_iter().next()) {
+            Some(item) => Ok(item),
+            None => Err(ChannelNotFoundError {
+                channel_id: channel_id.to_string(),
+            }
+            .into()),
+        }
+    }
+
+    pub async fn get_channels_details(
+        &self,
+        channel_ids: &[String],
+    ) -> Result<Vec<YoutubeStatisticsItem>, Error> {
+        let mut items = vec![];
+
+        for chunk in channel_ids.chunks(MAX_IDS_PER_REQUEST) {
+            let api_key = self.apikey_repo.get_least_used_api_key().await?;
+
+            let url = format!(
+                "{}channels?part=snippet,brandingSettings,statistics&maxResults={}&id={}&key={}",
+                BASE_URL,
+                MAX_IDS_PER_REQUEST,
+                chunk.join(","),
+                api_key.key
+            );
+
+            let resp = self
+                .get_json::<YouTubeChannelDetails>(url, &api_key, DEFAULT_QUOTA_COST)
+                .await?;
+
+            items.extend(resp.items.unwrap_or_default());
+        }
+
+        Ok(items)
+    }
+
+    /// Channels listed as featured channels in the branding settings of a channel.
+    pub async fn get_featured_channels_urls(&self, channel_id: &str) -> Result<Vec<String>, Error> {
+        let featured_channels_urls = self
+            .get_channel_details(channel_id)
             .await?
-            .json::<YouTubeChannelDetails>()
+            .branding_settings
+            .channel
+            .featured_channels_urls
+            .unwrap_or_default();
+
+        Ok(featured_channels_urls)
+    }
+
+    /// Channels listed in the channel sections of a channel.
+    pub async fn get_channel_section_channel_ids(
+        &self,
+        channel_id: &str,
+    ) -> Result<Vec<String>, Error> {
+        let api_key = self.apikey_repo.get_least_used_api_key().await?;
+
+        let url = format!(
+            "{}channelSections?part=snippet,contentDetails&channelId={}&key={}",
+            BASE_URL, channel_id, api_key.key
+        );
+
+        let resp = self
+            .get_json::<YoutubeChannelSections>(url, &api_key, DEFAULT_QUOTA_COST)
+            .await?;
+
+        let channel_ids = resp
+            .items
+            .into_iter()
+            .filter_map(|item| item.content_details)
+            .flat_map(|content_details| content_details.channels.unwrap_or_default())
+            .collect::<Vec<String>>();
+
+        Ok(channel_ids)
+    }
+
+    pub async fn resolve_channel_reference(
+        &self,
+        reference: &ChannelReference,
+    ) -> Result<Option<String>, Error> {
+        match reference {
+            ChannelReference::ChannelId(channel_id) => Ok(Some(channel_id.to_string())),
+            ChannelReference::Handle(handle) => self.find_channel_id("forHandle", handle).await,
+            ChannelReference::Username(username) => {
+                self.find_channel_id("forUsername", username).await
+            }
+            ChannelReference::CustomUrl(custom_url) => {
+                find_channel_id_on_page(&format!("{}c/{}", YOUTUBE_BASE_URL, custom_url)).await
+            }
+            ChannelReference::Video(video_id) => self.find_video_channel_id(video_id).await,
+        }
+    }
+
+    /// Loads snippet, statistics and content details of up to 50 videos with a single
+    /// request. Videos that are private or deleted are missing from the result.
+    pub async fn get_videos_details(
+        &self,
+        video_ids: &[String],
+    ) -> Result<Vec<YoutubeVideoItem>, Error> {
+        let api_key = self.apikey_repo.get_least_used_api_key().await?;
+
+        let url = format!(
+            "{}videos?part=snippet,statistics,contentDetails,liveStreamingDetails&maxResults={}&id={}&key={}",
+            BASE_URL,
+            MAX_IDS_PER_REQUEST,
+            video_ids.join(","),
+            api_key.key
+        );
+
+        let resp = self
+            .get_json::<YoutubeVideoDetails>(url, &api_key, DEFAULT_QUOTA_COST)
+            .await?;
+
+        Ok(resp.items)
+    }
+
+    /// Shorts are served under `/shorts/`, all other videos redirect to the watch page.
+    /// Any other answer, e.g. rate limiting or a consent page, is an error.
+    pub async fn is_short(&self, video_id: &str) -> Result<bool, Error> {
+        let response = self
+            .no_redirect_client
+            .head(format!("{}shorts/{}", YOUTUBE_BASE_URL, video_id))
+            .send()
+            .await?;
+
+        let status = response.status();
+
+        if status.is_success() {
+            return Ok(true);
+        }
+
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|location| location.to_str().ok())
+            .unwrap_or_default();
+
+        if status.is_redirection() && location.contains("/watch") {
+            return Ok(false);
+        }
+
+        Err(anyhow!(
+            "Unexpected response {} checking short {} (location: {})",
+            status,
+            video_id,
+            location
+        ))
+    }
+
+    async fn find_video_channel_id(&self, video_id: &str) -> Result<Option<String>, Error> {
+        let api_key = self.apikey_repo.get_least_used_api_key().await?;
+
+        let url = format!(
+            "{}videos?part=snippet&id={}&key={}",
+            BASE_URL, video_id, api_key.key
+        );
+
+        let resp = self
+            .get_json::<YoutubeVideoDetails>(url, &api_key, DEFAULT_QUOTA_COST)
+            .await?;
+
+        let channel_id = resp
+            .items
+            .into_iter()
+            .next()
+            .map(|item| item.snippet.channel_id);
+
+        Ok(channel_id)
+    }
+
+    async fn find_channel_id(&self, filter: &str, value: &str) -> Result<Option<String>, Error> {
+        let api_key = self.apikey_repo.get_least_used_api_key().await?;
+
+        let url = Url::parse_with_params(
+            &format!("{}channels", BASE_URL),
+            &[
+                ("part", "id"),
+                (filter, value),
+                ("key", api_key.key.as_str()),
+            ],
+        )?;
+
+        let resp = self
+            .get_json::<YoutubeIdList>(url, &api_key, DEFAULT_QUOTA_COST)
             .await?;
 
-        self.apikey_repo.update_usage(&api_key).await?;
+        let channel_id = resp
+            .items
+            .unwrap_or_default()
+            .into_iter()
+            .next()
+            .map(|item| item.id);
 
-        match resp.items {
-            Some(items) => Ok(items[0].clone()),
-            None => Err(Error::msg("No items found")),
+        Ok(channel_id)
+    }
+
+    pub async fn search_channel_ids(
+        &self,
+        term: &str,
+        language: &str,
+        search_type: &str,
+        published_after: Option<DateTime<Utc>>,
+    ) -> Result<Vec<String>, Error> {
+        let api_key = self.apikey_repo.get_least_used_api_key().await?;
+
+        let mut params = vec![
+            ("part", "snippet".to_string()),
+            ("maxResults", MAX_IDS_PER_REQUEST.to_string()),
+            ("q", term.to_string()),
+            ("relevanceLanguage", language.to_string()),
+            ("type", search_type.to_string()),
+            ("key", api_key.key.to_string()),
+        ];
+
+        if let Some(published_after) = published_after {
+            params.push((
+                "publishedAfter",
+                published_after.to_rfc3339_opts(SecondsFormat::Secs, true),
+            ));
         }
+
+        let url = Url::parse_with_params(&format!("{}search", BASE_URL), &params)?;
+
+        let resp = self
+            .get_json::<YoutubeSearchResults>(url, &api_key, SEARCH_QUOTA_COST)
+            .await?;
+
+        let mut channel_ids = resp
+            .items
+            .into_iter()
+            .map(|item| item.snippet.channel_id)
+            .collect::<Vec<String>>();
+
+        channel_ids.sort();
+        channel_ids.dedup();
+
+        Ok(channel_ids)
     }
 
+    /// Loads all subscriptions of a channel. The first page is requested with
+    /// `known_etag`, and `None` is returned when the API answers that it is unchanged.
+    /// The etag of the first page does not cover changes on further pages, so the
+    /// list only has an etag when it fits on a single page.
     pub async fn get_channel_subscriptions(
         &self,
         channel_id: &str,
-    ) -> Result<Vec<YouTubeChannelSubscriptionSnippet>, Error> {
+        known_etag: Option<&str>,
+    ) -> Result<Option<SubscriptionList>, Error> {
         let mut page_token: Option<String> = None;
+        let mut etag: Option<String> = None;
         let mut snippets = vec![];
+        let mut pages = 0;
 
         loop {
-            let response = self
-                .get_channel_subscriptions_page(channel_id, page_token)
-                .await?;
+            let if_none_match = if pages == 0 { known_etag } else { None };
+            let response = match self
+                .get_channel_subscriptions_page(channel_id, page_token, if_none_match)
+                .await?
+            {
+                Some(response) => response,
+                None => return Ok(None),
+            };
+
+            if pages == 0 {
+                etag = Some(response.etag.clone());
+            }
+
+            pages += 1;
 
             let response_snippets = response
                 .items
@@ -72,14 +380,19 @@ impl YoutubeService {
             }
         }
 
-        Ok(snippets)
+        Ok(Some(SubscriptionList {
+            etag: if pages == 1 { etag } else { None },
+            snippets,
+        }))
     }
 
+    /// Returns `None` when `if_none_match` is given and the page is unchanged.
     async fn get_channel_subscriptions_page(
         &self,
         channel_id: &str,
         page_token: Option<String>,
-    ) -> Result<YoutubeChannelSubscriptions, Error> {
+        if_none_match: Option<&str>,
+    ) -> Result<Option<YoutubeChannelSubscriptions>, Error> {
         let api_key = self.apikey_repo.get_least_used_api_key().await?;
 
         let mut url = format!(
@@ -87,17 +400,100 @@ impl YoutubeService {
             BASE_URL, channel_id, api_key.key
         );
 
-        if page_token.is_some() {
-            url = format!("{}&pageToken={}", url, page_token.unwrap());
+        if let Some(page_token) = page_token {
+            url = format!("{}&pageToken={}", url, page_token);
         }
 
-        let resp = reqwest::get(url)
-            .await?
-            .json::<YoutubeChannelSubscriptions>()
-            .await?;
+        let mut request = reqwest::Client::new().get(url);
+
+        if let Some(if_none_match) = if_none_match {
+            request = request.header(IF_NONE_MATCH, if_none_match);
+        }
+
+        let response = request.send().await?;
 
-        self.apikey_repo.update_usage(&api_key).await?;
+        self.update_usage(&api_key, DEFAULT_QUOTA_COST).await?;
 
-        Ok(resp)
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+
+        let resp = parse_response::<YoutubeChannelSubscriptions>(response).await?;
+
+        Ok(Some(resp))
+    }
+
+    /// Loads a page of the uploads playlist of a channel, which contains the complete
+    /// upload history, newest first. Every page costs one quota unit.
+    pub async fn get_uploads_page(
+        &self,
+        channel_id: &str,
+        page_token: Option<String>,
+    ) -> Result<YoutubePlaylistItems, Error> {
+        let playlist_id = match channel_id.strip_prefix("UC") {
+            Some(suffix) if is_valid_channel_id(channel_id) => format!("UU{}", suffix),
+            _ => return Err(anyhow!("Invalid channel id {}", channel_id)),
+        };
+
+        let api_key = self.apikey_repo.get_least_used_api_key().await?;
+
+        let mut url = format!(
+            "{}playlistItems?part=snippet,contentDetails&maxResults={}&playlistId={}&key={}",
+            BASE_URL, MAX_IDS_PER_REQUEST, playlist_id, api_key.key
+        );
+
+        if let Some(page_token) = page_token {
+            url = format!("{}&pageToken={}", url, page_token);
+        }
+
+        self.get_json::<YoutubePlaylistItems>(url, &api_key, DEFAULT_QUOTA_COST)
+            .await
     }
+
+    /// Sends a request and counts its cost as soon as it was answered, since failed
+    /// requests use quota as well.
+    async fn get_json<T: DeserializeOwned>(
+        &self,
+        url: impl IntoUrl,
+        api_key: &ApiKey,
+        cost: i32,
+    ) -> Result<T, Error> {
+        let response = reqwest::get(url).await?;
+
+        self.update_usage(api_key, cost).await?;
+
+        parse_response::<T>(response).await
+    }
+
+    async fn update_usage(&self, api_key: &ApiKey, cost: i32) -> Result<(), Error> {
+        self.used_quota.fetch_add(cost as i64, Ordering::Relaxed);
+        self.apikey_repo.update_usage(api_key, cost).await
+    }
+}
+
+async fn parse_response<T: DeserializeOwned>(response: Response) -> Result<T, Error> {
+    if !response.status().is_success() {
+        let error_response = response.json::<YoutubeErrorResponse>().await?;
+        return Err(error_response.error.into());
+    }
+
+    let resp = response.json::<T>().await?;
+
+    Ok(resp)
+}
+
+async fn find_channel_id_on_page(page_url: &str) -> Result<Option<String>, Error> {
+    let response = reqwest::get(page_url).await?;
+
+    if response.status() != 200 {
+        return Ok(None);
+    }
+
+    let html = response.text().await?;
+
+    let channel_id = CANONICAL_CHANNEL_REGEX
+        .captures(&html)
+        .map(|cap| cap[1].to_string());
+
+    Ok(channel_id)
 }
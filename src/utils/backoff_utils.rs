@@ -0,0 +1,30 @@
+use crate::utils::consts::ONE_DAYS_IN_SECONDS;
+
+/// Exponential backoff starting at one day after the first failure and doubling
+/// with every further failure, capped at `max_backoff_days`.
+pub fn get_backoff_seconds(failures: i32, max_backoff_days: i64) -> i64 {
+    let backoff_days = 2_i64
+        .pow(failures.clamp(1, 31) as u32 - 1)
+        .min(max_backoff_days);
+
+    backoff_days * ONE_DAYS_IN_SECONDS as i64
+}
+
+#[cfg(test)]
+mod tests {
+    const DAY: i64 = 86400;
+
+    #[test]
+    fn double_backoff_per_failure() {
+        assert_eq!(super::get_backoff_seconds(1, 64), DAY);
+        assert_eq!(super::get_backoff_seconds(2, 64), 2 * DAY);
+        assert_eq!(super::get_backoff_seconds(5, 64), 16 * DAY);
+    }
+
+    #[test]
+    fn cap_backoff() {
+        assert_eq!(super::get_backoff_seconds(7, 64), 64 * DAY);
+        assert_eq!(super::get_backoff_seconds(100, 64), 64 * DAY);
+        assert_eq!(super::get_backoff_seconds(0, 64), DAY);
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ChannelReference {
+    ChannelId(String),
+    Handle(String),
+    CustomUrl(String),
+    Username(String),
+    Video(String),
+}
+
+static CHANNEL_ID_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^UC[0-9A-Za-z_-]{22}$").unwrap());
+static HANDLE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^@([0-9A-Za-z._-]{3,30})$").unwrap());
+static VIDEO_URL_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?:youtube\.com/(?:watch\?(?:.*&)?v=|shorts/|live/|embed/)|youtu\.be/)([0-9A-Za-z_-]{11})",
+    )
+    .unwrap()
+});
+static CHANNEL_ID_URL_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"youtube\.com/channel/(UC[0-9A-Za-z_-]{22})").unwrap());
+static HANDLE_URL_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"youtube\.com/@([0-9A-Za-z._-]+)").unwrap());
+static CUSTOM_URL_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"youtube\.com/c/([^/?#\s]+)").unwrap());
+static USERNAME_URL_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"youtube\.com/user/([^/?#\s]+)").unwrap());
+static MENTION_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:^|[\s(\[,;:])@([0-9A-Za-z][0-9A-Za-z._-]{2,29})").unwrap());
+
+pub fn is_valid_channel_id(channel_id: &str) -> bool {
+    CHANNEL_ID_REGEX.is_match(channel_id)
+}
+
+/// Parses a single user submitted channel reference, which can be a raw channel id,
+/// a `@handle` or any channel or video url.
+pub fn parse_channel_reference(input: &str) -> Option<ChannelReference> {
+    let input = input.trim();
+
+    if is_valid_channel_id(input) {
+        return Some(ChannelReference::ChannelId(input.to_string()));
+    }
+
+    if let Some(cap) = HANDLE_REGEX.captures(input) {
+        return Some(ChannelReference::Handle(sanitize_handle(&cap[1])));
+    }
+
+    if let Some(cap) = VIDEO_URL_REGEX.captures(input) {
+        return Some(ChannelReference::Video(cap[1].to_string()));
+    }
+
+    parse_channel_references(input).into_iter().next()
+}
+
+pub fn parse_channel_references(text: &str) -> Vec<ChannelReference> {
+    let mut seen = HashSet::new();
+    let mut references = vec![];
+
+    let mut add = |reference: ChannelReference| {
+        if seen.insert(reference.clone()) {
+            references.push(reference);
+        }
+    };
+
+    for cap in CHANNEL_ID_URL_REGEX.captures_iter(text) {
+        add(ChannelReference::ChannelId(cap[1].to_string()));
+    }
+
+    for cap in HANDLE_URL_REGEX
+        .captures_iter(text)
+        .chain(MENTION_REGEX.captures_iter(text))
+    {
+        add(ChannelReference::Handle(sanitize_handle(&cap[1])));
+    }
+
+    for cap in CUSTOM_URL_REGEX.captures_iter(text) {
+        add(ChannelReference::CustomUrl(cap[1].to_string()));
+    }
+
+    for cap in USERNAME_URL_REGEX.captures_iter(text) {
+        add(ChannelReference::Username(cap[1].to_string()));
+    }
+
+    references
+}
+
+fn sanitize_handle(handle: &str) -> String {
+    handle.trim_end_matches(&['.', '-'][..]).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChannelReference;
+
+    #[test]
+    fn parse_channel_id_links() {
+        let references = super::parse_channel_references(
+            "Check out https://www.youtube.com/channel/UCabcdefghijklmnopqrstuv for more",
+        );
+
+        assert_eq!(
+            references,
+            vec![ChannelReference::ChannelId(
+                "UCabcdefghijklmnopqrstuv".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn parse_handles_and_mentions() {
+        let references = super::parse_channel_references(
+            "Sailing with @SailingXYZ. Also see https://youtube.com/@OtherBoat and @sailingxyz",
+        );
+
+        assert_eq!(
+            references,
+            vec![
+                ChannelReference::Handle("otherboat".to_string()),
+                ChannelReference::Handle("sailingxyz".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignore_email_addresses() {
+        let references = super::parse_channel_references("Contact us at crew@sailingboat.com");
+
+        assert!(references.is_empty());
+    }
+
+    #[test]
+    fn parse_submitted_channel_ids_and_handles() {
+        assert_eq!(
+            super::parse_channel_reference(" UCabcdefghijklmnopqrstuv "),
+            Some(ChannelReference::ChannelId(
+                "UCabcdefghijklmnopqrstuv".to_string()
+            ))
+        );
+        assert_eq!(
+            super::parse_channel_reference("@SailingBoat"),
+            Some(ChannelReference::Handle("sailingboat".to_string()))
+        );
+        assert_eq!(
+            super::parse_channel_reference("https://www.youtube.com/@SailingBoat/videos"),
+            Some(ChannelReference::Handle("sailingboat".to_string()))
+        );
+    }
+
+    #[test]
+    fn parse_submitted_video_urls() {
+        let expected = Some(ChannelReference::Video("dQw4w9WgXcQ".to_string()));
+
+        assert_eq!(
+            super::parse_channel_reference("https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=10"),
+            expected
+        );
+        assert_eq!(
+            super::parse_channel_reference("https://youtu.be/dQw4w9WgXcQ"),
+            expected
+        );
+        assert_eq!(
+            super::parse_channel_reference("https://youtube.com/shorts/dQw4w9WgXcQ"),
+            expected
+        );
+    }
+
+    #[test]
+    fn reject_invalid_submissions() {
+        assert_eq!(super::parse_channel_reference("UCtooshort"), None);
+        assert_eq!(super::parse_channel_reference("sailing boat"), None);
+        assert!(!super::is_valid_channel_id("UCabcdefghijklmnopqrstu!"));
+    }
+
+    #[test]
+    fn parse_custom_urls_and_usernames() {
+        let references = super::parse_channel_references(
+            "youtube.com/c/SailingBoat/videos and https://www.youtube.com/user/oldsailor?sub=1",
+        );
+
+        assert_eq!(
+            references,
+            vec![
+                ChannelReference::CustomUrl("SailingBoat".to_string()),
+                ChannelReference::Username("oldsailor".to_string()),
+            ]
+        );
+    }
+}
@@ -1,2 +1,3 @@
 pub const DEVELOPMENT: &str = "development";
 pub const ONE_DAYS_IN_SECONDS: u64 = 86400;
+pub const DORMANT_AFTER_WEEKS: i64 = 52;
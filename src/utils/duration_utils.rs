@@ -0,0 +1,55 @@
+use regex::Regex;
+
+/// Parses an ISO 8601 duration as returned by the YouTube API, e.g. `PT1H2M3S` or
+/// `P1DT2H`, into seconds. Durations with years or months are not supported.
+pub fn parse_iso8601_duration(duration: &str) -> Option<i64> {
+    let regex =
+        Regex::new(r"^P(?:(\d+)W)?(?:(\d+)D)?(?:T(?:(\d+)H)?(?:(\d+)M)?(?:(\d+)S)?)?$").unwrap();
+
+    let cap = regex.captures(duration)?;
+
+    if duration == "P" || duration.ends_with('T') {
+        return None;
+    }
+
+    let units = [7 * 86400, 86400, 3600, 60, 1];
+    let seconds = units
+        .iter()
+        .enumerate()
+        .map(|(i, unit)| {
+            cap.get(i + 1)
+                .and_then(|value| value.as_str().parse::<i64>().ok())
+                .unwrap_or(0)
+                * unit
+        })
+        .sum();
+
+    Some(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn parse_time_durations() {
+        assert_eq!(super::parse_iso8601_duration("PT15S"), Some(15));
+        assert_eq!(super::parse_iso8601_duration("PT4M13S"), Some(253));
+        assert_eq!(super::parse_iso8601_duration("PT1H0M5S"), Some(3605));
+        assert_eq!(super::parse_iso8601_duration("PT2H"), Some(7200));
+    }
+
+    #[test]
+    fn parse_day_durations() {
+        assert_eq!(super::parse_iso8601_duration("P1DT2H"), Some(93600));
+        assert_eq!(super::parse_iso8601_duration("P0D"), Some(0));
+        assert_eq!(super::parse_iso8601_duration("P1W"), Some(604800));
+    }
+
+    #[test]
+    fn reject_invalid_durations() {
+        assert_eq!(super::parse_iso8601_duration(""), None);
+        assert_eq!(super::parse_iso8601_duration("P"), None);
+        assert_eq!(super::parse_iso8601_duration("PT"), None);
+        assert_eq!(super::parse_iso8601_duration("P1Y"), None);
+        assert_eq!(super::parse_iso8601_duration("1H"), None);
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+/// Computes PageRank scores for `nodes` over the directed `edges` (from, to). Edges
+/// with an unknown endpoint are ignored. Scores are scaled so that the average
+/// node has a score of 1.0.
+pub fn page_rank(
+    nodes: &[String],
+    edges: &[(String, String)],
+    damping: f64,
+    iterations: usize,
+) -> HashMap<String, f64> {
+    let node_count = nodes.len();
+    if node_count == 0 {
+        return HashMap::new();
+    }
+
+    let index: HashMap<&str, usize> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.as_str(), i))
+        .collect();
+
+    let mut outgoing: Vec<Vec<usize>> = vec![vec![]; node_count];
+    for (from, to) in edges {
+        if let (Some(&from), Some(&to)) = (index.get(from.as_str()), index.get(to.as_str())) {
+            if from != to && !outgoing[from].contains(&to) {
+                outgoing[from].push(to);
+            }
+        }
+    }
+
+    let n = node_count as f64;
+    let mut scores = vec![1.0 / n; node_count];
+
+    for _ in 0..iterations {
+        let dangling_sum: f64 = outgoing
+            .iter()
+            .enumerate()
+            .filter(|(_, targets)| targets.is_empty())
+            .map(|(i, _)| scores[i])
+            .sum();
+
+        let base = (1.0 - damping) / n + damping * dangling_sum / n;
+        let mut next_scores = vec![base; node_count];
+
+        for (from, targets) in outgoing.iter().enumerate() {
+            let share = damping * scores[from] / targets.len() as f64;
+            for &to in targets {
+                next_scores[to] += share;
+            }
+        }
+
+        scores = next_scores;
+    }
+
+    nodes
+        .iter()
+        .zip(scores)
+        .map(|(node, score)| (node.clone(), score * n))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    fn edge(from: &str, to: &str) -> (String, String) {
+        (from.to_string(), to.to_string())
+    }
+
+    #[test]
+    fn rank_most_subscribed_channel_highest() {
+        let nodes = ids(&["a", "b", "c", "d"]);
+        let edges = vec![
+            edge("a", "d"),
+            edge("b", "d"),
+            edge("c", "d"),
+            edge("d", "a"),
+        ];
+
+        let scores = super::page_rank(&nodes, &edges, 0.85, 50);
+
+        assert!(scores["d"] > scores["a"]);
+        assert!(scores["a"] > scores["b"]);
+        assert!((scores["b"] - scores["c"]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn average_score_is_one() {
+        let nodes = ids(&["a", "b", "c"]);
+        let edges = vec![edge("a", "b"), edge("x", "c")];
+
+        let scores = super::page_rank(&nodes, &edges, 0.85, 50);
+        let total: f64 = scores.values().sum();
+
+        assert!((total - 3.0).abs() < 1e-9);
+    }
+}
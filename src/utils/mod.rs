@@ -1,3 +1,12 @@
+pub mod backoff_utils;
+pub mod channel_reference_utils;
 pub mod consts;
 pub mod db;
+pub mod duration_utils;
+pub mod graph_utils;
 pub mod keyword_utils;
+pub mod polling_utils;
+pub mod priority_queue;
+pub mod video_feed_parser;
+pub mod video_format_utils;
+pub mod video_history_utils;
@@ -0,0 +1,104 @@
+/// Number of checks per expected upload interval.
+const POLLS_PER_UPLOAD_INTERVAL: i64 = 4;
+
+/// Derives how many seconds to wait before the next video feed poll of a channel
+/// from the publish timestamps of its latest videos. Channels that upload often or
+/// uploaded recently are polled more often; channels that went quiet back off
+/// until `max_interval` is reached.
+pub fn get_next_poll_interval(
+    publish_timestamps: &[i64],
+    now: i64,
+    min_interval: i64,
+    max_interval: i64,
+) -> i64 {
+    let mut timestamps = publish_timestamps.to_vec();
+    timestamps.sort_unstable_by(|a, b| b.cmp(a));
+
+    let last_upload = match timestamps.first() {
+        Some(&last_upload) => last_upload,
+        None => return max_interval,
+    };
+
+    let mut gaps = timestamps
+        .windows(2)
+        .map(|pair| pair[0] - pair[1])
+        .collect::<Vec<i64>>();
+    gaps.sort_unstable();
+
+    let since_last_upload = (now - last_upload).max(0);
+    let expected_gap = match gaps.get(gaps.len() / 2) {
+        Some(&median_gap) => median_gap.max(since_last_upload),
+        None => since_last_upload,
+    };
+
+    (expected_gap / POLLS_PER_UPLOAD_INTERVAL).clamp(min_interval, max_interval)
+}
+
+/// Seconds to wait before polling a channel again whose feed failed to load,
+/// doubling `min_interval` with every consecutive failure up to `max_interval`.
+pub fn get_failure_backoff_interval(failures: i32, min_interval: i64, max_interval: i64) -> i64 {
+    min_interval
+        .saturating_mul(2_i64.pow(failures.clamp(1, 31) as u32 - 1))
+        .min(max_interval)
+}
+
+#[cfg(test)]
+mod tests {
+    const HOUR: i64 = 3600;
+    const DAY: i64 = 24 * HOUR;
+    const NOW: i64 = 1_700_000_000;
+
+    #[test]
+    fn poll_active_channels_often() {
+        let timestamps = (1..10).map(|i| NOW - i * DAY).collect::<Vec<i64>>();
+
+        let interval = super::get_next_poll_interval(&timestamps, NOW, HOUR, 7 * DAY);
+
+        assert_eq!(interval, 6 * HOUR);
+    }
+
+    #[test]
+    fn back_off_for_quiet_channels() {
+        let timestamps = vec![NOW - 400 * DAY, NOW - 401 * DAY, NOW - 402 * DAY];
+
+        let interval = super::get_next_poll_interval(&timestamps, NOW, HOUR, 7 * DAY);
+
+        assert_eq!(interval, 7 * DAY);
+    }
+
+    #[test]
+    fn clamp_to_configured_bounds() {
+        let timestamps = vec![NOW - HOUR, NOW - 2 * HOUR, NOW - 3 * HOUR];
+
+        assert_eq!(
+            super::get_next_poll_interval(&timestamps, NOW, HOUR, 7 * DAY),
+            HOUR
+        );
+        assert_eq!(
+            super::get_next_poll_interval(&[], NOW, HOUR, 7 * DAY),
+            7 * DAY
+        );
+    }
+
+    #[test]
+    fn double_interval_per_failure() {
+        assert_eq!(super::get_failure_backoff_interval(1, HOUR, 7 * DAY), HOUR);
+        assert_eq!(
+            super::get_failure_backoff_interval(3, HOUR, 7 * DAY),
+            4 * HOUR
+        );
+        assert_eq!(super::get_failure_backoff_interval(0, HOUR, 7 * DAY), HOUR);
+    }
+
+    #[test]
+    fn cap_failure_interval() {
+        assert_eq!(
+            super::get_failure_backoff_interval(9, HOUR, 7 * DAY),
+            7 * DAY
+        );
+        assert_eq!(
+            super::get_failure_backoff_interval(100, HOUR, 7 * DAY),
+            7 * DAY
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::VecDeque;
+
+/// Queue with a fixed number of priority lanes, lane `0` being the most important one.
+/// A non-empty lane is passed over at most `starvation_limit` times in a row, so lower
+/// lanes still make progress while higher lanes are busy.
+pub struct PriorityQueue<T> {
+    lanes: Vec<VecDeque<T>>,
+    skipped: Vec<usize>,
+    starvation_limit: usize,
+}
+
+impl<T> PriorityQueue<T> {
+    pub fn new(lane_count: usize, starvation_limit: usize) -> PriorityQueue<T> {
+        PriorityQueue {
+            lanes: (0..lane_count).map(|_| VecDeque::new()).collect(),
+            skipped: vec![0; lane_count],
+            starvation_limit,
+        }
+    }
+
+    pub fn push(&mut self, lane: usize, item: T) {
+        let lane = lane.min(self.lanes.len() - 1);
+
+        self.lanes[lane].push_back(item);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let non_empty_lanes = (0..self.lanes.len())
+            .filter(|&lane| !self.lanes[lane].is_empty())
+            .collect::<Vec<usize>>();
+
+        let selected_lane = non_empty_lanes
+            .iter()
+            .find(|&&lane| self.skipped[lane] >= self.starvation_limit)
+            .or_else(|| non_empty_lanes.first())
+            .copied()?;
+
+        for lane in non_empty_lanes {
+            if lane == selected_lane {
+                self.skipped[lane] = 0;
+            } else {
+                self.skipped[lane] += 1;
+            }
+        }
+
+        self.lanes[selected_lane].pop_front()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lanes.iter().map(|lane| lane.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PriorityQueue;
+
+    #[test]
+    fn pop_higher_lanes_first() {
+        let mut queue = PriorityQueue::new(3, 10);
+
+        queue.push(2, "discovery");
+        queue.push(1, "update");
+        queue.push(0, "interactive");
+        queue.push(1, "update 2");
+
+        assert_eq!(queue.pop(), Some("interactive"));
+        assert_eq!(queue.pop(), Some("update"));
+        assert_eq!(queue.pop(), Some("update 2"));
+        assert_eq!(queue.pop(), Some("discovery"));
+        assert_eq!(queue.pop(), None);
+        assert_eq!(queue.len(), 0);
+    }
+
+    #[test]
+    fn serve_starving_lanes() {
+        let mut queue = PriorityQueue::new(3, 2);
+
+        for i in 0..6 {
+            queue.push(0, i);
+        }
+        queue.push(2, 100);
+
+        let order = (0..7).map(|_| queue.pop().unwrap()).collect::<Vec<i32>>();
+
+        assert_eq!(order, vec![0, 1, 100, 2, 3, 4, 5]);
+    }
+}
@@ -0,0 +1,525 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::DateTime;
+use log::warn;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+
+use crate::models::youtube_video_feed_response::{
+    Author, Entry, Link, MediaStarRating, MediaStatistics, MediaThumbnail, YoutubeVideoFeedResponse,
+};
+
+const ATOM_NAMESPACE: &[u8] = b"http://www.w3.org/2005/Atom";
+const YOUTUBE_NAMESPACE: &[u8] = b"http://www.youtube.com/xml/schemas/2015";
+const MEDIA_NAMESPACE: &[u8] = b"http://search.yahoo.com/mrss/";
+
+#[derive(Debug)]
+pub enum FeedParseError {
+    Xml(quick_xml::Error),
+    UnexpectedEof,
+    MissingElement(&'static str),
+    InvalidValue { name: &'static str, value: String },
+}
+
+impl fmt::Display for FeedParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeedParseError::Xml(e) => write!(f, "Invalid feed xml: {}", e),
+            FeedParseError::UnexpectedEof => write!(f, "Feed ended unexpectedly"),
+            FeedParseError::MissingElement(name) => write!(f, "Feed misses element {}", name),
+            FeedParseError::InvalidValue { name, value } => {
+                write!(f, "Feed contains invalid {}: {}", name, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FeedParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FeedParseError::Xml(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<quick_xml::Error> for FeedParseError {
+    fn from(e: quick_xml::Error) -> Self {
+        FeedParseError::Xml(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Namespace {
+    Atom,
+    Youtube,
+    Media,
+    Other,
+}
+
+impl Namespace {
+    fn from_bytes(namespace: Option<&[u8]>) -> Namespace {
+        match namespace {
+            Some(ATOM_NAMESPACE) => Namespace::Atom,
+            Some(YOUTUBE_NAMESPACE) => Namespace::Youtube,
+            Some(MEDIA_NAMESPACE) => Namespace::Media,
+            _ => Namespace::Other,
+        }
+    }
+}
+
+type Element = (Namespace, String);
+
+/// Parses a channel video feed (`feeds/videos.xml`). Elements are matched by their
+/// namespace instead of their prefix, unknown elements are skipped and optional
+/// elements may be missing. Entries without video id or valid dates and optional
+/// blocks with invalid values are skipped with a warning.
+pub fn parse_video_feed(xml: &str) -> Result<YoutubeVideoFeedResponse, FeedParseError> {
+    let mut reader = Reader::from_str(xml);
+
+    let mut buf = vec![];
+    let mut namespace_buf = vec![];
+    let mut path: Vec<Element> = vec![];
+    let mut has_feed = false;
+    let mut entry: Option<Entry> = None;
+    let mut entries = vec![];
+
+    loop {
+        let (namespace, event) = reader.read_namespaced_event(&mut buf, &mut namespace_buf)?;
+        let namespace = Namespace::from_bytes(namespace);
+
+        match event {
+            Event::Start(ref element) | Event::Empty(ref element) => {
+                let name = reader.decode(element.local_name())?.to_string();
+
+                if path.is_empty() {
+                    if namespace != Namespace::Atom || name != "feed" {
+                        return Err(FeedParseError::MissingElement("feed"));
+                    }
+
+                    has_feed = true;
+                }
+
+                if path.len() == 1 && namespace == Namespace::Atom && name == "entry" {
+                    entry = Some(Entry::default());
+                }
+
+                if let Some(entry) = entry.as_mut() {
+                    read_attributes(entry, namespace, &name, element, &reader)?;
+                }
+
+                if let Event::Start(_) = event {
+                    path.push((namespace, name));
+                }
+            }
+            Event::Text(ref text) => {
+                let text = text.unescape_and_decode(&reader)?;
+
+                if let Some(entry) = entry.as_mut() {
+                    read_text(entry, &path, text);
+                }
+            }
+            Event::CData(ref text) => {
+                let text = text.unescape_and_decode(&reader)?;
+
+                if let Some(entry) = entry.as_mut() {
+                    read_text(entry, &path, text);
+                }
+            }
+            Event::End(_) => {
+                let element = path.pop();
+
+                if path.len() == 1 && element == Some((Namespace::Atom, "entry".to_string())) {
+                    if let Some(entry) = entry.take() {
+                        match validate_entry(entry) {
+                            Ok(entry) => entries.push(entry),
+                            Err(e) => warn!("Skip invalid feed entry: {}", e),
+                        }
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+
+        buf.clear();
+    }
+
+    if !path.is_empty() {
+        return Err(FeedParseError::UnexpectedEof);
+    }
+
+    if !has_feed {
+        return Err(FeedParseError::MissingElement("feed"));
+    }
+
+    Ok(YoutubeVideoFeedResponse { entries })
+}
+
+fn read_attributes(
+    entry: &mut Entry,
+    namespace: Namespace,
+    name: &str,
+    element: &BytesStart,
+    reader: &Reader<&[u8]>,
+) -> Result<(), FeedParseError> {
+    match (namespace, name) {
+        (Namespace::Atom, "author") => {
+            entry.author = Some(Author::default());
+        }
+        (Namespace::Atom, "link") => {
+            let rel = get_attribute(element, reader, b"rel")?;
+
+            if entry.link.is_none() || rel.as_deref() == Some("alternate") {
+                if let Some(href) = get_attribute(element, reader, b"href")? {
+                    entry.link = Some(Link { rel, href });
+                }
+            }
+        }
+        (Namespace::Media, "thumbnail") => {
+            let thumbnail = read_thumbnail(element, reader);
+            entry.group.thumbnail = skip_invalid(&entry.video_id, thumbnail);
+        }
+        (Namespace::Media, "starRating") => {
+            let star_rating = read_star_rating(element, reader);
+            entry.group.community.star_rating = skip_invalid(&entry.video_id, star_rating);
+        }
+        (Namespace::Media, "statistics") => {
+            let statistics = parse_attribute(element, reader, b"views", "views")
+                .map(|views| views.map(|views| MediaStatistics { views }));
+
+            entry.group.community.statistics = skip_invalid(&entry.video_id, statistics);
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn read_thumbnail(
+    element: &BytesStart,
+    reader: &Reader<&[u8]>,
+) -> Result<Option<MediaThumbnail>, FeedParseError> {
+    let url = match get_attribute(element, reader, b"url")? {
+        Some(url) => url,
+        None => return Ok(None),
+    };
+
+    Ok(Some(MediaThumbnail {
+        url,
+        width: parse_attribute(element, reader, b"width", "thumbnail width")?,
+        height: parse_attribute(element, reader, b"height", "thumbnail height")?,
+    }))
+}
+
+fn read_star_rating(
+    element: &BytesStart,
+    reader: &Reader<&[u8]>,
+) -> Result<Option<MediaStarRating>, FeedParseError> {
+    let count = parse_attribute(element, reader, b"count", "star rating count")?;
+    let average = parse_attribute(element, reader, b"average", "star rating average")?;
+
+    Ok(count
+        .zip(average)
+        .map(|(count, average)| MediaStarRating { count, average }))
+}
+
+/// Text can be split across several text and CDATA events, so it is appended to
+/// the target field untrimmed. The complete value is trimmed once the entry ends.
+fn read_text(entry: &mut Entry, path: &[Element], text: String) {
+    let (namespace, name) = match path.last() {
+        Some((namespace, name)) => (*namespace, name.as_str()),
+        None => return,
+    };
+
+    let parent = path
+        .len()
+        .checked_sub(2)
+        .and_then(|i| path.get(i))
+        .map(|(namespace, name)| (*namespace, name.as_str()));
+
+    match (namespace, name, parent) {
+        (Namespace::Youtube, "videoId", _) => entry.video_id.push_str(&text),
+        (Namespace::Atom, "title", Some((Namespace::Atom, "entry"))) => entry.title.push_str(&text),
+        (Namespace::Atom, "published", Some((Namespace::Atom, "entry"))) => {
+            entry.published.push_str(&text)
+        }
+        (Namespace::Atom, "updated", Some((Namespace::Atom, "entry"))) => {
+            entry.updated.push_str(&text)
+        }
+        (Namespace::Atom, "name", Some((Namespace::Atom, "author"))) => {
+            if let Some(author) = entry.author.as_mut() {
+                author.name.push_str(&text);
+            }
+        }
+        (Namespace::Atom, "uri", Some((Namespace::Atom, "author"))) => {
+            if let Some(author) = entry.author.as_mut() {
+                append_text(&mut author.uri, text);
+            }
+        }
+        (Namespace::Media, "title", _) => append_text(&mut entry.group.title, text),
+        (Namespace::Media, "description", _) => entry.group.description.push_str(&text),
+        _ => {}
+    }
+}
+
+fn append_text(target: &mut Option<String>, text: String) {
+    match target {
+        Some(target) => target.push_str(&text),
+        None => *target = Some(text),
+    }
+}
+
+/// Drops an optional block with invalid values instead of failing the whole feed.
+fn skip_invalid<T>(video_id: &str, block: Result<Option<T>, FeedParseError>) -> Option<T> {
+    block.unwrap_or_else(|e| {
+        warn!("Skip invalid value of video {}: {}", video_id, e);
+        None
+    })
+}
+
+fn validate_entry(mut entry: Entry) -> Result<Entry, FeedParseError> {
+    trim_entry(&mut entry);
+
+    if entry.video_id.is_empty() {
+        return Err(FeedParseError::MissingElement("yt:videoId"));
+    }
+
+    if entry.published.is_empty() {
+        return Err(FeedParseError::MissingElement("published"));
+    }
+
+    validate_date(&entry.published, "published")?;
+
+    if entry.updated.is_empty() {
+        entry.updated = entry.published.clone();
+    }
+
+    validate_date(&entry.updated, "updated")?;
+
+    if entry
+        .author
+        .as_ref()
+        .is_some_and(|author| author.name.is_empty())
+    {
+        entry.author = None;
+    }
+
+    Ok(entry)
+}
+
+fn trim_entry(entry: &mut Entry) {
+    trim(&mut entry.video_id);
+    trim(&mut entry.title);
+    trim(&mut entry.published);
+    trim(&mut entry.updated);
+    trim(&mut entry.group.description);
+
+    if let Some(title) = entry.group.title.as_mut() {
+        trim(title);
+    }
+
+    if let Some(author) = entry.author.as_mut() {
+        trim(&mut author.name);
+
+        if let Some(uri) = author.uri.as_mut() {
+            trim(uri);
+        }
+    }
+}
+
+fn trim(text: &mut String) {
+    let trimmed = text.trim();
+
+    if trimmed.len() != text.len() {
+        *text = trimmed.to_string();
+    }
+}
+
+fn validate_date(value: &str, name: &'static str) -> Result<(), FeedParseError> {
+    match DateTime::parse_from_rfc3339(value) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(FeedParseError::InvalidValue {
+            name,
+            value: value.to_string(),
+        }),
+    }
+}
+
+fn get_attribute(
+    element: &BytesStart,
+    reader: &Reader<&[u8]>,
+    key: &[u8],
+) -> Result<Option<String>, FeedParseError> {
+    for attribute in element.attributes() {
+        let attribute = attribute?;
+
+        if attribute.key == key {
+            return Ok(Some(attribute.unescape_and_decode_value(reader)?));
+        }
+    }
+
+    Ok(None)
+}
+
+fn parse_attribute<T: FromStr>(
+    element: &BytesStart,
+    reader: &Reader<&[u8]>,
+    key: &[u8],
+    name: &'static str,
+) -> Result<Option<T>, FeedParseError> {
+    match get_attribute(element, reader, key)? {
+        Some(value) => match value.parse::<T>() {
+            Ok(parsed) => Ok(Some(parsed)),
+            Err(_) => Err(FeedParseError::InvalidValue { name, value }),
+        },
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FeedParseError;
+
+    #[test]
+    fn parse_complete_feed() {
+        let feed =
+            super::parse_video_feed(include_str!("../../tests/fixtures/video_feed.xml")).unwrap();
+
+        assert_eq!(feed.entries.len(), 2);
+
+        let entry = &feed.entries[0];
+        assert_eq!(entry.video_id, "dQw4w9WgXcQ");
+        assert_eq!(entry.title, "Crossing the Atlantic: media: & more");
+        assert_eq!(entry.published, "2022-03-01T12:00:00+00:00");
+        assert_eq!(entry.updated, "2022-03-02T08:30:00+00:00");
+        assert_eq!(
+            entry.link.as_ref().map(|link| link.href.as_str()),
+            Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ")
+        );
+        assert_eq!(
+            entry.author.as_ref().map(|author| author.name.as_str()),
+            Some("Sailing Boat")
+        );
+        assert_eq!(
+            entry.group.description,
+            "Day 12 at sea.\nWatch: https://youtube.com/@sailingboat"
+        );
+
+        let thumbnail = entry.group.thumbnail.as_ref().unwrap();
+        assert_eq!(
+            thumbnail.url,
+            "https://i1.ytimg.com/vi/dQw4w9WgXcQ/hqdefault.jpg"
+        );
+        assert_eq!(thumbnail.width, Some(480));
+
+        let star_rating = entry.group.community.star_rating.as_ref().unwrap();
+        assert_eq!(star_rating.count, 250);
+        assert!((star_rating.average - 4.9).abs() < f64::EPSILON);
+        assert_eq!(
+            entry.group.community.statistics.as_ref().map(|s| s.views),
+            Some(12345)
+        );
+    }
+
+    #[test]
+    fn tolerate_missing_optional_elements() {
+        let feed =
+            super::parse_video_feed(include_str!("../../tests/fixtures/video_feed.xml")).unwrap();
+
+        let entry = &feed.entries[1];
+        assert_eq!(entry.video_id, "9bZkp7q19f0");
+        assert_eq!(entry.updated, entry.published);
+        assert_eq!(entry.group.description, "");
+        assert_eq!(entry.group.thumbnail, None);
+        assert_eq!(entry.group.community.star_rating, None);
+        assert_eq!(entry.group.community.statistics, None);
+    }
+
+    #[test]
+    fn parse_feed_without_entries() {
+        let feed =
+            super::parse_video_feed(include_str!("../../tests/fixtures/video_feed_empty.xml"))
+                .unwrap();
+
+        assert!(feed.entries.is_empty());
+    }
+
+    #[test]
+    fn skip_invalid_entries_and_values() {
+        let missing_video_id = super::parse_video_feed(include_str!(
+            "../../tests/fixtures/video_feed_missing_video_id.xml"
+        ))
+        .unwrap();
+        assert_eq!(missing_video_id.entries.len(), 1);
+        assert_eq!(missing_video_id.entries[0].video_id, "9bZkp7q19f0");
+
+        let invalid_views = super::parse_video_feed(include_str!(
+            "../../tests/fixtures/video_feed_invalid_views.xml"
+        ))
+        .unwrap();
+        assert_eq!(invalid_views.entries.len(), 1);
+        assert_eq!(invalid_views.entries[0].group.community.statistics, None);
+        assert!(invalid_views.entries[0]
+            .group
+            .community
+            .star_rating
+            .is_some());
+    }
+
+    #[test]
+    fn append_text_split_across_events() {
+        let feed = super::parse_video_feed(
+            r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:yt="http://www.youtube.com/xml/schemas/2015">
+                <entry>
+                    <yt:videoId>dQw4w9WgXcQ</yt:videoId>
+                    <title> Sailing &amp; <![CDATA[<Diving>]]> </title>
+                    <published>2022-03-01T12:00:00+00:00</published>
+                </entry>
+            </feed>"#,
+        )
+        .unwrap();
+
+        assert_eq!(feed.entries[0].title, "Sailing & <Diving>");
+    }
+
+    #[test]
+    fn skip_entries_with_invalid_dates() {
+        let feed = super::parse_video_feed(
+            r#"<feed xmlns="http://www.w3.org/2005/Atom" xmlns:yt="http://www.youtube.com/xml/schemas/2015">
+                <entry>
+                    <yt:videoId>dQw4w9WgXcQ</yt:videoId>
+                    <published>yesterday</published>
+                </entry>
+                <entry>
+                    <yt:videoId>9bZkp7q19f0</yt:videoId>
+                    <published>2022-03-01T12:00:00+00:00</published>
+                    <updated>2022-13-01</updated>
+                </entry>
+                <entry>
+                    <yt:videoId>kJQP7kiw5Fk</yt:videoId>
+                    <published>2022-03-01T12:00:00+00:00</published>
+                </entry>
+            </feed>"#,
+        )
+        .unwrap();
+
+        assert_eq!(feed.entries.len(), 1);
+        assert_eq!(feed.entries[0].video_id, "kJQP7kiw5Fk");
+    }
+
+    #[test]
+    fn reject_malformed_feeds() {
+        let truncated = super::parse_video_feed(include_str!(
+            "../../tests/fixtures/video_feed_truncated.xml"
+        ));
+        assert!(matches!(
+            truncated,
+            Err(FeedParseError::UnexpectedEof) | Err(FeedParseError::Xml(_))
+        ));
+
+        let html = super::parse_video_feed("<html><body>Not found</body></html>");
+        assert!(matches!(html, Err(FeedParseError::MissingElement("feed"))));
+    }
+}
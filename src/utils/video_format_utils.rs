@@ -0,0 +1,106 @@
+/// Shorts can be up to three minutes long.
+pub const MAX_SHORT_DURATION_SECONDS: i64 = 180;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoFormat {
+    Short,
+    Live,
+    Premiere,
+    Upload,
+}
+
+impl VideoFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VideoFormat::Short => "short",
+            VideoFormat::Live => "live",
+            VideoFormat::Premiere => "premiere",
+            VideoFormat::Upload => "upload",
+        }
+    }
+}
+
+/// Checks whether a video link, e.g. from the video feed, points to a short.
+pub fn is_short_url(url: &str) -> bool {
+    url.contains("youtube.com/shorts/")
+}
+
+/// Classifies a video by its duration in seconds, its live broadcast state and
+/// whether it is reachable as a short. Livestreams and premieres both come with
+/// live streaming details, but only premieres of an already uploaded video have a
+/// duration before they start. After a premiere aired it looks like a finished
+/// livestream, so a video that was classified as premiere before stays one.
+pub fn classify_video_format(
+    duration: Option<i64>,
+    live_broadcast_content: Option<&str>,
+    has_live_streaming_details: bool,
+    is_short: bool,
+    was_premiere: bool,
+) -> VideoFormat {
+    let duration = duration.unwrap_or(0);
+
+    if has_live_streaming_details {
+        if was_premiere || (live_broadcast_content == Some("upcoming") && duration > 0) {
+            return VideoFormat::Premiere;
+        }
+
+        return VideoFormat::Live;
+    }
+
+    if is_short && duration <= MAX_SHORT_DURATION_SECONDS {
+        return VideoFormat::Short;
+    }
+
+    VideoFormat::Upload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::VideoFormat;
+
+    #[test]
+    fn classify_live_streams_and_premieres() {
+        assert_eq!(
+            super::classify_video_format(Some(5400), Some("none"), true, false, false),
+            VideoFormat::Live
+        );
+        assert_eq!(
+            super::classify_video_format(Some(0), Some("upcoming"), true, false, false),
+            VideoFormat::Live
+        );
+        assert_eq!(
+            super::classify_video_format(Some(900), Some("upcoming"), true, false, false),
+            VideoFormat::Premiere
+        );
+        assert_eq!(
+            super::classify_video_format(Some(900), Some("none"), true, false, true),
+            VideoFormat::Premiere
+        );
+    }
+
+    #[test]
+    fn classify_shorts_and_uploads() {
+        assert_eq!(
+            super::classify_video_format(Some(45), Some("none"), false, true, false),
+            VideoFormat::Short
+        );
+        assert_eq!(
+            super::classify_video_format(Some(45), Some("none"), false, false, false),
+            VideoFormat::Upload
+        );
+        assert_eq!(
+            super::classify_video_format(Some(1200), None, false, true, false),
+            VideoFormat::Upload
+        );
+    }
+
+    #[test]
+    fn detect_short_urls() {
+        assert!(super::is_short_url(
+            "https://www.youtube.com/shorts/dQw4w9WgXcQ"
+        ));
+        assert!(!super::is_short_url(
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ"
+        ));
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{models::youtube_video_feed_response::Entry, repos::video_repo::VideoVersion};
+
+/// Lists the fields of a feed entry that differ from the stored version of the
+/// video. Titles and descriptions are compared with normalized whitespace, since
+/// the feed and the uploads playlist format line breaks differently. Thumbnails are
+/// not compared, as their feed url is derived from the video id and stays the same
+/// when the thumbnail is replaced.
+pub fn get_changed_fields(version: &VideoVersion, entry: &Entry) -> Vec<&'static str> {
+    let mut changed_fields = vec![];
+
+    if normalize_whitespace(&version.title) != normalize_whitespace(&entry.title) {
+        changed_fields.push("title");
+    }
+
+    if normalize_whitespace(&version.description) != normalize_whitespace(&entry.group.description)
+    {
+        changed_fields.push("description");
+    }
+
+    changed_fields
+}
+
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::youtube_video_feed_response::{Entry, MediaGroup},
+        repos::video_repo::VideoVersion,
+    };
+
+    fn version(title: &str, description: &str) -> VideoVersion {
+        VideoVersion {
+            title: title.to_string(),
+            description: description.to_string(),
+            feed_updated_at: None,
+        }
+    }
+
+    fn entry(title: &str, description: &str) -> Entry {
+        Entry {
+            title: title.to_string(),
+            group: MediaGroup {
+                description: description.to_string(),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn detect_changed_fields() {
+        let changed_fields = super::get_changed_fields(
+            &version("Old title", "Old description"),
+            &entry("New title", "New description"),
+        );
+
+        assert_eq!(changed_fields, vec!["title", "description"]);
+    }
+
+    #[test]
+    fn ignore_whitespace_differences() {
+        let changed_fields = super::get_changed_fields(
+            &version("Sailing  home", "Line one\r\nLine two\n"),
+            &entry("Sailing home", "Line one\nLine two"),
+        );
+
+        assert!(changed_fields.is_empty());
+    }
+}